@@ -105,6 +105,7 @@ pub fn convert_key_code(event: KeyEvent) -> Vec<KeyCode> {
         event::KeyCode::Char('x') => vec![KeyCode::X],
         event::KeyCode::Char('y') => vec![KeyCode::Y],
         event::KeyCode::Char('z') => vec![KeyCode::Z],
+        event::KeyCode::Char(' ') => vec![KeyCode::Space],
         event::KeyCode::Char(',') => vec![KeyCode::Comma],
         event::KeyCode::Char('.') => vec![KeyCode::Period],
         event::KeyCode::Char('/') => vec![KeyCode::Slash],
@@ -120,10 +121,14 @@ pub fn convert_key_code(event: KeyEvent) -> Vec<KeyCode> {
         event::KeyCode::Char('-') => vec![KeyCode::Minus],
         event::KeyCode::Char('=') => vec![KeyCode::Equals],
         event::KeyCode::Char('+') => vec![KeyCode::Plus],
+        // Shifted keys that `KeyCode` has no variant for, as on a US keyboard.
+        event::KeyCode::Char('<') => vec![KeyCode::Comma, KeyCode::LShift],
+        event::KeyCode::Char('>') => vec![KeyCode::Period, KeyCode::LShift],
+        event::KeyCode::Char('?') => vec![KeyCode::Slash, KeyCode::LShift],
         event::KeyCode::Esc => vec![KeyCode::Escape],
         _ => vec![],
     };
-    if event.modifiers.intersects(KeyModifiers::SHIFT) {
+    if event.modifiers.intersects(KeyModifiers::SHIFT) && !key_codes.contains(&KeyCode::LShift) {
         key_codes.push(KeyCode::LShift);
     }
     if event.modifiers.intersects(KeyModifiers::CONTROL) {
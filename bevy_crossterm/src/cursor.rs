@@ -0,0 +1,7 @@
+/// Where the mouse cursor is on the terminal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Cursor {
+    /// The column and the row of the cell under the cursor, or `None` until
+    /// the mouse moves over the terminal.
+    pub position: Option<(u16, u16)>,
+}
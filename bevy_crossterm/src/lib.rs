@@ -1,10 +1,12 @@
 use bevy_app::{AppBuilder, Plugin};
 
 mod converter;
+mod cursor;
 mod runner;
 mod terminal;
 
 pub use crossterm;
+pub use cursor::Cursor;
 pub use runner::crossterm_runner;
 pub use terminal::Terminal;
 
@@ -16,6 +18,7 @@ impl Plugin for CrosstermPlugin {
         if app.resources().get::<Terminal>().is_none() {
             app.init_resource::<Terminal>();
         }
+        app.init_resource::<Cursor>();
         app.set_runner(crossterm_runner);
     }
 }
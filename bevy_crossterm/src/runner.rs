@@ -1,4 +1,4 @@
-use crate::{converter::convert_key_code, Terminal};
+use crate::{converter::convert_key_code, Cursor, Terminal};
 use bevy_app::{App, AppExit, EventReader, Events};
 use bevy_input::{
     keyboard::{KeyCode, KeyboardInput},
@@ -40,6 +40,11 @@ fn event_loop(app: &mut App) -> Result<()> {
             if let Event::Key(key_event) = event {
                 new_key_codes = convert_key_code(key_event);
             }
+
+            if let Event::Mouse(mouse_event) = event {
+                let mut cursor = app.resources.get_mut::<Cursor>().unwrap();
+                cursor.position = Some((mouse_event.column, mouse_event.row));
+            }
         }
 
         {
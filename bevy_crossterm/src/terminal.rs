@@ -1,6 +1,7 @@
 use crossterm::{
     cursor::{Hide, MoveTo, Show},
-    style::{Attributes, Colors, ContentStyle, PrintStyledContent, ResetColor, StyledContent},
+    event::{DisableMouseCapture, EnableMouseCapture},
+    style::{Attributes, Color, Colors, ContentStyle, PrintStyledContent, ResetColor, StyledContent},
     terminal::{self, EnterAlternateScreen, LeaveAlternateScreen, SetSize, SetTitle},
     ExecutableCommand, QueueableCommand, Result,
 };
@@ -30,6 +31,7 @@ impl Terminal {
         stdout
             .queue(EnterAlternateScreen)?
             .queue(Hide)?
+            .queue(EnableMouseCapture)?
             .queue(SetTitle(title))?;
         stdout.flush()?;
 
@@ -47,6 +49,7 @@ impl Terminal {
         terminal::disable_raw_mode()?;
         self.stdout
             .queue(Show)?
+            .queue(DisableMouseCapture)?
             .queue(ResetColor)?
             .queue(LeaveAlternateScreen)?;
         self.stdout.flush()?;
@@ -97,9 +100,14 @@ impl Terminal {
         content: S,
         style: ContentStyle,
     ) {
+        if !self.is_on_screen(x, y) {
+            return;
+        }
         let index = self.pos_to_index(x, y);
         let string = content.to_string();
-        for (item, glyph) in self.new_buffer.iter_mut().skip(index).zip(string.chars()) {
+        // Clipped at the end of the line.
+        let glyphs = string.chars().take((self.size.0 - x) as usize);
+        for (item, glyph) in self.new_buffer.iter_mut().skip(index).zip(glyphs) {
             item.glyph = Some(glyph);
             item.style = style;
         }
@@ -110,6 +118,9 @@ impl Terminal {
     }
 
     pub fn put_char_with_style(&mut self, x: u16, y: u16, glyph: char, style: ContentStyle) {
+        if !self.is_on_screen(x, y) {
+            return;
+        }
         let index = self.pos_to_index(x, y);
         let item = &mut self.new_buffer[index];
         item.glyph = Some(glyph);
@@ -129,6 +140,15 @@ impl Terminal {
         self.put_char_with_style(x, y, glyph, ContentStyle::default())
     }
 
+    /// Changes the background color at a position, keeping what is there.
+    pub fn set_background_color(&mut self, x: u16, y: u16, color: Option<Color>) {
+        if !self.is_on_screen(x, y) {
+            return;
+        }
+        let index = self.pos_to_index(x, y);
+        self.new_buffer[index].style.background_color = color;
+    }
+
     pub fn flush(&mut self) -> Result<()> {
         for i in 0..self.old_buffer.len() {
             if self.old_buffer[i] != self.new_buffer[i] {
@@ -147,6 +167,12 @@ impl Terminal {
         Ok(())
     }
 
+    /// Drawing off the screen, e.g., after the terminal shrank, does
+    /// nothing.
+    fn is_on_screen(&self, x: u16, y: u16) -> bool {
+        x < self.size.0 && y < self.size.1
+    }
+
     fn pos_to_index(&self, x: u16, y: u16) -> usize {
         (y * self.size.0 + x) as usize
     }
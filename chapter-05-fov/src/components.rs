@@ -1,4 +1,5 @@
 use bevy_crossterm::crossterm::style::Color;
+use roguelike_algorithms::fov::VisibilityGrid;
 
 #[derive(Clone, Debug)]
 pub struct Position {
@@ -18,7 +19,7 @@ pub struct Player {}
 
 #[derive(Clone, Debug)]
 pub struct Viewshed {
-    pub visible_tiles: Vec<(i16, i16)>,
+    pub visible_tiles: VisibilityGrid,
     pub range: i16,
     pub dirty: bool,
 }
@@ -10,6 +10,7 @@ use bevy_crossterm::{
     CrosstermPlugin, Terminal,
 };
 use rand::{rngs::StdRng, SeedableRng};
+use roguelike_algorithms::fov::VisibilityGrid;
 
 mod components;
 mod map;
@@ -32,7 +33,7 @@ fn spawn_player(commands: &mut Commands, map: Res<Map>) {
         },
         Player {},
         Viewshed {
-            visible_tiles: Vec::new(),
+            visible_tiles: VisibilityGrid::new(map.width as usize, map.height as usize),
             range: 8,
             dirty: true,
         },
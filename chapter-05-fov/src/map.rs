@@ -5,7 +5,7 @@ use bevy_crossterm::{
     Terminal,
 };
 use rand::{rngs::StdRng, Rng};
-use roguelike_algorithms::fov::Map2D;
+use roguelike_algorithms::{fov::Map2D, Point};
 use std::cmp::{max, min};
 
 #[derive(PartialEq, Copy, Clone, Debug)]
@@ -110,12 +110,12 @@ impl FromResources for Map {
 }
 
 impl Map2D for Map {
-    fn is_opaque(&self, (x, y): (i16, i16)) -> bool {
+    fn is_opaque(&self, Point { x, y }: Point) -> bool {
         let idx = self.xy_idx(x, y);
         self.tiles[idx] == TileType::Wall
     }
 
-    fn is_in_bound(&self, (x, y): (i16, i16)) -> bool {
+    fn is_in_bound(&self, Point { x, y }: Point) -> bool {
         x >= 0 && x < self.width && y >= 0 && y < self.height
     }
 }
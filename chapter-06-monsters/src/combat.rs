@@ -0,0 +1,153 @@
+use crate::{
+    components::{
+        CombatStats, DefenseBonus, Equipped, MeleePowerBonus, Name, Player, Position, WantsToMelee,
+    },
+    game_log::{name_of, GameLog},
+    noise::{Noises, COMBAT_VOLUME},
+    run_state::{RunState, Turn},
+    save_load::delete_save,
+};
+use bevy::prelude::*;
+use bevy_crossterm::crossterm::style::Color;
+
+/// The stage resolving what the actors want to do in the turn stage, in
+/// every state, so that the intents inserted there are already in the
+/// world.
+pub const COMBAT_STAGE: &str = "combat";
+
+/// Sent when `target` takes `amount` damage.
+#[derive(Clone, Copy, Debug)]
+pub struct Damage {
+    pub target: Entity,
+    pub amount: i32,
+}
+
+/// The power and defense bonuses of everything `owner` has equipped.
+#[allow(clippy::type_complexity)]
+fn equipment_bonuses(
+    owner: Entity,
+    equipment: &Query<(&Equipped, Option<&MeleePowerBonus>, Option<&DefenseBonus>)>,
+) -> (i32, i32) {
+    equipment
+        .iter()
+        .filter(|(equipped, _, _)| equipped.owner == owner)
+        .fold(
+            (0, 0),
+            |(power, defense), (_, power_bonus, defense_bonus)| {
+                (
+                    power + power_bonus.map_or(0, |bonus| bonus.power),
+                    defense + defense_bonus.map_or(0, |bonus| bonus.defense),
+                )
+            },
+        )
+}
+
+/// Resolves the melee attacks: the target takes the power of the attacker
+/// minus its defense, both with the bonuses of their equipment. Fights are
+/// loud.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn melee_combat_system(
+    commands: &mut Commands,
+    mut damage: ResMut<Events<Damage>>,
+    mut log: ResMut<GameLog>,
+    turn: Res<Turn>,
+    mut noises: ResMut<Noises>,
+    attackers: Query<(Entity, &WantsToMelee, &CombatStats, Option<&Player>)>,
+    targets: Query<&CombatStats>,
+    equipment: Query<(&Equipped, Option<&MeleePowerBonus>, Option<&DefenseBonus>)>,
+    names: Query<&Name>,
+    positions: Query<&Position>,
+) {
+    for (entity, wants_melee, stats, player) in attackers.iter() {
+        commands.remove_one::<WantsToMelee>(entity);
+        // The attacker may have died earlier this turn.
+        if stats.hp <= 0 {
+            continue;
+        }
+        let target_stats = match targets.get(wants_melee.target) {
+            Ok(target_stats) if target_stats.hp > 0 => target_stats,
+            _ => continue,
+        };
+        let (power_bonus, _) = equipment_bonuses(entity, &equipment);
+        let (_, defense_bonus) = equipment_bonuses(wants_melee.target, &equipment);
+        let amount = (stats.power + power_bonus - target_stats.defense - defense_bonus).max(0);
+
+        // Only the player fights, and only monsters fight the player.
+        let (message, color) = match (player.is_some(), amount > 0) {
+            (true, true) => {
+                let target = name_of(wants_melee.target, &names);
+                (format!("You hit the {}.", target), Color::White)
+            }
+            (true, false) => {
+                let target = name_of(wants_melee.target, &names);
+                (format!("You fail to hurt the {}.", target), Color::Grey)
+            }
+            (false, true) => {
+                let attacker = name_of(entity, &names);
+                (format!("The {} hits you.", attacker), Color::Red)
+            }
+            (false, false) => {
+                let attacker = name_of(entity, &names);
+                (format!("The {} fails to hurt you.", attacker), Color::Grey)
+            }
+        };
+        log.add(turn.0, message, color);
+        if let Ok(pos) = positions.get(wants_melee.target) {
+            noises.make((pos.x, pos.y), COMBAT_VOLUME);
+        }
+
+        if amount > 0 {
+            damage.send(Damage {
+                target: wants_melee.target,
+                amount,
+            });
+        }
+    }
+}
+
+pub fn damage_system(
+    mut event_reader: Local<EventReader<Damage>>,
+    events: Res<Events<Damage>>,
+    mut query: Query<&mut CombatStats>,
+) {
+    for event in event_reader.iter(&events) {
+        if let Ok(mut stats) = query.get_mut(event.target) {
+            stats.hp -= event.amount;
+        }
+    }
+}
+
+/// Removes the dead monsters, and ends the game if the player is dead,
+/// deleting the saved game.
+pub fn delete_the_dead_system(
+    commands: &mut Commands,
+    mut state: ResMut<State<RunState>>,
+    mut log: ResMut<GameLog>,
+    turn: Res<Turn>,
+    query: Query<(Entity, &CombatStats, Option<&Player>)>,
+    names: Query<&Name>,
+) {
+    for (entity, stats, player) in query.iter() {
+        if stats.hp > 0 {
+            continue;
+        }
+        if player.is_none() {
+            log.add(
+                turn.0,
+                format!("The {} dies.", name_of(entity, &names)),
+                Color::Yellow,
+            );
+            commands.despawn(entity);
+        } else if *state.current() != RunState::GameOver {
+            log.add(turn.0, "You die.", Color::Red);
+            if let Err(err) = delete_save() {
+                log.add(
+                    turn.0,
+                    format!("Could not delete the saved game: {}", err),
+                    Color::Red,
+                );
+            }
+            state.set_next(RunState::GameOver).unwrap();
+        }
+    }
+}
@@ -1,27 +1,155 @@
+use bevy::prelude::*;
 use bevy_crossterm::crossterm::style::Color;
+use roguelike_algorithms::fov::VisibilityGrid;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Position {
     pub x: i16,
     pub y: i16,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Renderable {
     pub glyph: char,
     pub fg: Option<Color>,
     pub bg: Option<Color>,
+    /// Entities of lower order are drawn over the others on the same tile.
+    pub render_order: i32,
 }
 
 #[derive(Clone, Debug)]
 pub struct Player {}
 
-#[derive(Clone, Debug)]
-pub struct Monster {}
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Monster {
+    /// Where the monster goes to look for the player: where it last saw
+    /// them, or where a noise seemed to come from. Forgotten once it gets
+    /// there.
+    pub destination: Option<(i16, i16)>,
+}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Viewshed {
-    pub visible_tiles: Vec<(i16, i16)>,
+    /// Not saved: recomputed whenever the entity is restored.
+    #[serde(skip)]
+    pub visible_tiles: VisibilityGrid,
     pub range: i16,
+    #[serde(skip)]
     pub dirty: bool,
 }
+
+/// Nothing else can stand on the same tile as the entity.
+#[derive(Clone, Debug)]
+pub struct BlocksTile;
+
+/// The energy an actor gains per tick. `100` is normal speed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Speed(pub i32);
+
+/// The energy an actor has. It acts whenever it has at least
+/// `ENERGY_THRESHOLD`, and each action spends some.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Energy(pub i32);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CombatStats {
+    pub max_hp: i32,
+    pub hp: i32,
+    /// The damage dealt by a melee attack, before defense.
+    pub power: i32,
+    /// The damage taken off every melee attack received.
+    pub defense: i32,
+}
+
+/// The entity wants to attack `target` in melee, this turn.
+#[derive(Clone, Debug)]
+pub struct WantsToMelee {
+    pub target: Entity,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Name {
+    pub name: String,
+}
+
+/// Can be picked up.
+#[derive(Clone, Debug)]
+pub struct Item;
+
+/// The item is carried by `owner`, and has no `Position`.
+#[derive(Clone, Debug)]
+pub struct InBackpack {
+    pub owner: Entity,
+}
+
+/// The item is used up when used.
+#[derive(Clone, Debug)]
+pub struct Consumable;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProvidesHealing {
+    pub heal_amount: i32,
+}
+
+/// The item deals `damage` to everything within `radius` of a target up to
+/// `range` away, or only to what is on the target if `radius` is `0`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InflictsDamage {
+    pub damage: i32,
+    pub range: i16,
+    pub radius: i16,
+}
+
+#[derive(Clone, Debug)]
+pub struct WantsToPickupItem {
+    pub item: Entity,
+}
+
+#[derive(Clone, Debug)]
+pub struct WantsToDropItem {
+    pub item: Entity,
+}
+
+#[derive(Clone, Debug)]
+pub struct WantsToUseItem {
+    pub item: Entity,
+    pub target: Option<(i16, i16)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum EquipmentSlot {
+    Melee,
+    Shield,
+}
+
+/// Using the item equips it in `slot`, replacing what was there.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Equippable {
+    pub slot: EquipmentSlot,
+}
+
+/// The item is worn or wielded by `owner`, and is not in their backpack.
+#[derive(Clone, Debug)]
+pub struct Equipped {
+    pub owner: Entity,
+    pub slot: EquipmentSlot,
+}
+
+/// Added to the power of the owner while equipped.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MeleePowerBonus {
+    pub power: i32,
+}
+
+/// Added to the defense of the owner while equipped.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DefenseBonus {
+    pub defense: i32,
+}
+
+/// The entity wants to unequip `item`, back into its backpack.
+#[derive(Clone, Debug)]
+pub struct WantsToRemoveItem {
+    pub item: Entity,
+}
@@ -0,0 +1,272 @@
+use crate::{
+    components::{
+        BlocksTile, CombatStats, Consumable, DefenseBonus, Energy, Equippable, InflictsDamage,
+        Item, MeleePowerBonus, Monster, Name, Player, Position, ProvidesHealing, Renderable, Speed,
+        Viewshed,
+    },
+    game_log::GameLog,
+    map::{Map, TileType},
+    map_builders::{MapBuilderKind, Prefabs},
+    run_state::Turn,
+    seed::Seed,
+    spawner,
+};
+use bevy::prelude::*;
+use bevy_crossterm::crossterm::style::Color;
+use roguelike_algorithms::fov::VisibilityGrid;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// An entity that is not in the world, with all the components it had:
+/// on a level that is not the current one, or in a save file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StoredEntity {
+    /// `None` for the items carried by the player.
+    pub position: Option<Position>,
+    pub renderable: Option<Renderable>,
+    pub monster: Option<Monster>,
+    pub viewshed: Option<Viewshed>,
+    pub speed: Option<Speed>,
+    pub energy: Option<Energy>,
+    pub combat_stats: Option<CombatStats>,
+    pub blocks_tile: bool,
+    pub name: Option<Name>,
+    pub item: bool,
+    pub consumable: bool,
+    pub provides_healing: Option<ProvidesHealing>,
+    pub inflicts_damage: Option<InflictsDamage>,
+    pub equippable: Option<Equippable>,
+    pub melee_power_bonus: Option<MeleePowerBonus>,
+    pub defense_bonus: Option<DefenseBonus>,
+}
+
+/// The components kept in a `StoredEntity`, besides the position and the
+/// viewshed, as queried.
+pub type StoredComponents<'a> = (
+    (
+        Option<&'a Renderable>,
+        Option<&'a Monster>,
+        Option<&'a Speed>,
+        Option<&'a Energy>,
+        Option<&'a CombatStats>,
+        Option<&'a BlocksTile>,
+    ),
+    (
+        Option<&'a Name>,
+        Option<&'a Item>,
+        Option<&'a Consumable>,
+        Option<&'a ProvidesHealing>,
+        Option<&'a InflictsDamage>,
+        Option<&'a Equippable>,
+        Option<&'a MeleePowerBonus>,
+        Option<&'a DefenseBonus>,
+    ),
+);
+
+impl StoredEntity {
+    pub fn new(
+        position: Option<Position>,
+        viewshed: Option<Viewshed>,
+        components: StoredComponents,
+    ) -> StoredEntity {
+        let (
+            (renderable, monster, speed, energy, combat_stats, blocks_tile),
+            (
+                name,
+                item,
+                consumable,
+                provides_healing,
+                inflicts_damage,
+                equippable,
+                melee_power_bonus,
+                defense_bonus,
+            ),
+        ) = components;
+        StoredEntity {
+            position,
+            renderable: renderable.cloned(),
+            monster: monster.cloned(),
+            viewshed,
+            speed: speed.cloned(),
+            energy: energy.cloned(),
+            combat_stats: combat_stats.cloned(),
+            blocks_tile: blocks_tile.is_some(),
+            name: name.cloned(),
+            item: item.is_some(),
+            consumable: consumable.is_some(),
+            provides_healing: provides_healing.cloned(),
+            inflicts_damage: inflicts_damage.cloned(),
+            equippable: equippable.cloned(),
+            melee_power_bonus: melee_power_bonus.cloned(),
+            defense_bonus: defense_bonus.cloned(),
+        }
+    }
+
+    /// Spawns the entity back on `map`. Its field of view is recomputed.
+    pub fn restore(self, commands: &mut Commands, map: &Map) -> Entity {
+        match self.position {
+            Some(position) => commands.spawn((position,)),
+            None => commands.spawn(()),
+        };
+        if let Some(renderable) = self.renderable {
+            commands.with(renderable);
+        }
+        if let Some(monster) = self.monster {
+            commands.with(monster);
+        }
+        if let Some(mut viewshed) = self.viewshed {
+            viewshed.visible_tiles = VisibilityGrid::new(map.width as usize, map.height as usize);
+            viewshed.dirty = true;
+            commands.with(viewshed);
+        }
+        if let Some(speed) = self.speed {
+            commands.with(speed);
+        }
+        if let Some(energy) = self.energy {
+            commands.with(energy);
+        }
+        if let Some(combat_stats) = self.combat_stats {
+            commands.with(combat_stats);
+        }
+        if self.blocks_tile {
+            commands.with(BlocksTile);
+        }
+        if let Some(name) = self.name {
+            commands.with(name);
+        }
+        if self.item {
+            commands.with(Item);
+        }
+        if self.consumable {
+            commands.with(Consumable);
+        }
+        if let Some(provides_healing) = self.provides_healing {
+            commands.with(provides_healing);
+        }
+        if let Some(inflicts_damage) = self.inflicts_damage {
+            commands.with(inflicts_damage);
+        }
+        if let Some(equippable) = self.equippable {
+            commands.with(equippable);
+        }
+        if let Some(melee_power_bonus) = self.melee_power_bonus {
+            commands.with(melee_power_bonus);
+        }
+        if let Some(defense_bonus) = self.defense_bonus {
+            commands.with(defense_bonus);
+        }
+        commands.current_entity().unwrap()
+    }
+}
+
+/// A level that is not the current one.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Level {
+    pub map: Map,
+    pub entities: Vec<StoredEntity>,
+}
+
+/// All levels of the dungeon. The current one lives in the `Map` resource
+/// and in the world; the others are stored here, exactly as they were left.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Dungeon {
+    /// The depth of the current level, `0` being the top one.
+    pub depth: u32,
+    levels: HashMap<u32, Level>,
+}
+
+/// Sent to move the player to another level.
+#[derive(Clone, Copy, Debug)]
+pub struct ChangeLevel {
+    pub depth: u32,
+}
+
+/// Stores the current level and switches to another one, restoring it if
+/// it has been visited, and generating it otherwise.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn change_level_system(
+    commands: &mut Commands,
+    mut event_reader: Local<EventReader<ChangeLevel>>,
+    events: Res<Events<ChangeLevel>>,
+    seed: Res<Seed>,
+    kind: Res<MapBuilderKind>,
+    prefabs: Res<Prefabs>,
+    mut dungeon: ResMut<Dungeon>,
+    mut map: ResMut<Map>,
+    mut log: ResMut<GameLog>,
+    turn: Res<Turn>,
+    mut query: Query<(
+        Entity,
+        &mut Position,
+        Option<&Player>,
+        Option<&mut Viewshed>,
+        StoredComponents,
+    )>,
+) {
+    let depth = match event_reader.latest(&events) {
+        Some(event) if event.depth != dungeon.depth => event.depth,
+        _ => return,
+    };
+    let going_down = depth > dungeon.depth;
+
+    let new_map = match dungeon.levels.remove(&depth) {
+        Some(level) => {
+            for stored in level.entities {
+                stored.restore(commands, &level.map);
+            }
+            level.map
+        }
+        None => {
+            let new_map = Map::generate(*seed, *kind, &prefabs, depth);
+            let mut rng = seed.rng(&format!("monsters {}", depth));
+            spawner::level_monsters(commands, &new_map, &mut rng);
+            let mut rng = seed.rng(&format!("items {}", depth));
+            spawner::level_items(commands, &new_map, &mut rng);
+            new_map
+        }
+    };
+
+    // Arrive on the stairs leading back to where the player came from.
+    let arrival = if going_down {
+        TileType::UpStairs
+    } else {
+        TileType::DownStairs
+    };
+    let (x, y) = new_map.find_tile(arrival).unwrap_or(new_map.start);
+
+    let mut entities = Vec::new();
+    for (entity, mut position, player, viewshed, components) in query.iter_mut() {
+        if player.is_some() {
+            position.x = x;
+            position.y = y;
+            if let Some(mut viewshed) = viewshed {
+                viewshed.dirty = true;
+            }
+        } else {
+            entities.push(StoredEntity::new(
+                Some(position.clone()),
+                viewshed.map(|viewshed| viewshed.clone()),
+                components,
+            ));
+            commands.despawn(entity);
+        }
+    }
+
+    let old_map = std::mem::replace(&mut *map, new_map);
+    let old_depth = dungeon.depth;
+    dungeon.levels.insert(
+        old_depth,
+        Level {
+            map: old_map,
+            entities,
+        },
+    );
+    dungeon.depth = depth;
+
+    let message = if going_down {
+        format!("You descend to depth {}.", depth + 1)
+    } else {
+        format!("You climb up to depth {}.", depth + 1)
+    };
+    log.add(turn.0, message, Color::Cyan);
+}
@@ -0,0 +1,135 @@
+use crate::{components::Name, key_bindings::KeyBindings, run_state::RunState};
+use bevy::prelude::*;
+use bevy_crossterm::{
+    crossterm::style::{Color, ContentStyle},
+    Terminal,
+};
+use serde::{Deserialize, Serialize};
+
+/// A message of the log, repeated `count` times in a row.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// The turn the message was last logged in.
+    pub turn: u32,
+    pub text: String,
+    pub color: Color,
+    pub count: u32,
+}
+
+impl LogEntry {
+    /// The entry as shown, e.g., `[12] You hit the goblin x3`.
+    pub fn line(&self) -> String {
+        if self.count > 1 {
+            format!("[{}] {} x{}", self.turn, self.text, self.count)
+        } else {
+            format!("[{}] {}", self.turn, self.text)
+        }
+    }
+
+    pub fn style(&self) -> ContentStyle {
+        ContentStyle {
+            foreground_color: Some(self.color),
+            ..ContentStyle::default()
+        }
+    }
+}
+
+/// Everything that happened, oldest first.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GameLog {
+    entries: Vec<LogEntry>,
+    /// How many lines the scrollback viewer is scrolled up from the end.
+    #[serde(skip)]
+    pub scroll: usize,
+}
+
+impl GameLog {
+    /// Logs a message, merging it into the last entry if it is the same.
+    pub fn add<S: Into<String>>(&mut self, turn: u32, text: S, color: Color) {
+        let text = text.into();
+        if let Some(last) = self.entries.last_mut() {
+            if last.text == text && last.color == color {
+                last.turn = turn;
+                last.count += 1;
+                return;
+            }
+        }
+        self.entries.push(LogEntry {
+            turn,
+            text,
+            color,
+            count: 1,
+        });
+    }
+
+    pub fn entries(&self) -> &[LogEntry] {
+        &self.entries
+    }
+}
+
+/// The name of an entity, as the log calls it.
+pub fn name_of(entity: Entity, names: &Query<&Name>) -> String {
+    match names.get(entity) {
+        Ok(name) => name.name.clone(),
+        Err(_) => "something".to_string(),
+    }
+}
+
+/// Scrolls the log with the page keys and the up and down movement keys, and
+/// closes it with Esc.
+pub fn log_input_system(
+    keys: Res<Input<KeyCode>>,
+    mut state: ResMut<State<RunState>>,
+    term: Res<Terminal>,
+    mut log: ResMut<GameLog>,
+    key_bindings: Res<KeyBindings>,
+) {
+    let page = term.size().1.saturating_sub(1).max(1) as usize;
+    let max_scroll = log.entries().len().saturating_sub(page);
+    if keys.pressed(KeyCode::Escape) {
+        log.scroll = 0;
+        state.set_next(RunState::AwaitingInput).unwrap();
+    } else if keys.pressed(KeyCode::PageUp) {
+        log.scroll = (log.scroll + page).min(max_scroll);
+    } else if keys.pressed(KeyCode::PageDown) {
+        log.scroll = log.scroll.saturating_sub(page);
+    } else {
+        match key_bindings.movement_delta(&keys) {
+            Some((_, delta_y)) if delta_y < 0 => log.scroll = (log.scroll + 1).min(max_scroll),
+            Some((_, delta_y)) if delta_y > 0 => log.scroll = log.scroll.saturating_sub(1),
+            _ => {}
+        }
+    }
+}
+
+/// Draws the whole log over the screen, scrolled up by `GameLog::scroll`.
+pub fn draw_log_viewer_system(
+    mut term: ResMut<Terminal>,
+    state: Res<State<RunState>>,
+    log: Res<GameLog>,
+) {
+    if *state.current() != RunState::ShowLog {
+        return;
+    }
+
+    term.cls();
+    let (_, height) = term.size();
+    let page = height.saturating_sub(1) as usize;
+    let entries = log.entries();
+    let end = entries.len().saturating_sub(log.scroll);
+    let start = end.saturating_sub(page);
+    for (i, entry) in entries[start..end].iter().enumerate() {
+        term.print_with_style(0, i as u16, entry.line(), entry.style());
+    }
+    let status = if entries.is_empty() {
+        "No messages yet. Esc to close.".to_string()
+    } else {
+        format!(
+            "Messages {}-{} of {}. Up/Down/PgUp/PgDn to scroll, Esc to close.",
+            start + 1,
+            end,
+            entries.len()
+        )
+    };
+    term.print(0, height.saturating_sub(1), status);
+}
@@ -0,0 +1,429 @@
+use crate::{
+    components::{
+        CombatStats, Energy, Equipped, InBackpack, InflictsDamage, Name, Player, Position,
+        WantsToDropItem, WantsToRemoveItem, WantsToUseItem,
+    },
+    dungeon::Dungeon,
+    game_log::GameLog,
+    initiative::{spend_player_energy, Action},
+    key_bindings::LETTER_KEYS,
+    map::Map,
+    reticle::Reticle,
+    run_state::{RunState, Turn},
+    seed::Seed,
+    spatial_index::SpatialIndex,
+};
+use bevy::prelude::*;
+use bevy_crossterm::{
+    crossterm::style::{Color, ContentStyle},
+    Cursor, Terminal,
+};
+
+/// The items `owner` carries, sorted by name, as listed in the menus.
+fn backpack(owner: Entity, items: &Query<(Entity, &Name, &InBackpack)>) -> Vec<(Entity, String)> {
+    let mut backpack: Vec<_> = items
+        .iter()
+        .filter(|(_, _, in_backpack)| in_backpack.owner == owner)
+        .map(|(item, name, _)| (item, name.name.clone()))
+        .collect();
+    backpack.sort_by(|(_, a), (_, b)| a.cmp(b));
+    backpack
+}
+
+/// What `owner` has equipped, sorted by slot, as listed in the menu.
+fn equipment(owner: Entity, items: &Query<(Entity, &Name, &Equipped)>) -> Vec<(Entity, String)> {
+    let mut equipment: Vec<_> = items
+        .iter()
+        .filter(|(_, _, equipped)| equipped.owner == owner)
+        .map(|(item, name, equipped)| (equipped.slot, item, name.name.clone()))
+        .collect();
+    equipment.sort_by_key(|&(slot, _, _)| slot);
+    equipment
+        .into_iter()
+        .map(|(slot, item, name)| (item, format!("{:?}: {}", slot, name)))
+        .collect()
+}
+
+/// The entry of a menu chosen with the pressed key, if any: the entries
+/// are chosen with the letters, in order.
+pub fn chosen_entry<'a, T>(keys: &Input<KeyCode>, entries: &'a [T]) -> Option<&'a T> {
+    LETTER_KEYS
+        .iter()
+        .position(|&key| keys.pressed(key))
+        .and_then(|i| entries.get(i))
+}
+
+/// The lines of a menu, each entry after its key.
+pub fn menu_lines<T>(entries: &[(T, String)], empty: &str) -> Vec<String> {
+    if entries.is_empty() {
+        return vec![empty.to_string()];
+    }
+    entries
+        .iter()
+        .zip(b'a'..=b'z')
+        .map(|((_, name), key)| format!("({}) {}", key as char, name))
+        .collect()
+}
+
+/// The width and the height of a box with a title and a list of lines,
+/// borders included.
+pub fn box_size(title: &str, lines: &[String]) -> (u16, u16) {
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .chain(Some(title.chars().count()))
+        .max()
+        .unwrap_or(0) as u16
+        + 4;
+    (width, lines.len() as u16 + 2)
+}
+
+/// Draws a box with a title and a list of lines, its top left corner at
+/// `(x, y)`.
+pub fn draw_box(term: &mut Terminal, x: u16, y: u16, title: &str, lines: &[String]) {
+    // Without the borders.
+    let width = box_size(title, lines).0 as usize - 2;
+    let border = "─".repeat(width);
+
+    term.print(x, y, format!("┌{}┐", border));
+    term.print(x + 2, y, title);
+    for (i, line) in lines.iter().enumerate() {
+        term.print(x, y + 1 + i as u16, format!("│ {:1$} │", line, width - 2));
+    }
+    term.print(x, y + 1 + lines.len() as u16, format!("└{}┘", border));
+}
+
+/// Draws a box with a title and a list of lines, at the middle of the map.
+pub fn draw_menu(term: &mut Terminal, map: &Map, title: &str, lines: &[String]) {
+    let (width, height) = box_size(title, lines);
+    let x = (map.width as u16).saturating_sub(width) / 2;
+    let y = (map.height as u16).saturating_sub(height) / 2;
+    draw_box(term, x, y, title, lines);
+}
+
+/// Lets the player choose an item in the inventory to use or to drop,
+/// or a target first if the item to use is ranged.
+#[allow(clippy::too_many_arguments)]
+pub fn item_menu_input_system(
+    commands: &mut Commands,
+    keys: Res<Input<KeyCode>>,
+    mut state: ResMut<State<RunState>>,
+    mut reticle: ResMut<Reticle>,
+    players: Query<(Entity, &Position), With<Player>>,
+    items: Query<(Entity, &Name, &InBackpack)>,
+    ranged: Query<&InflictsDamage>,
+    mut energy: Query<&mut Energy, With<Player>>,
+) {
+    if keys.pressed(KeyCode::Escape) {
+        state.set_next(RunState::AwaitingInput).unwrap();
+        return;
+    }
+
+    for (player, pos) in players.iter() {
+        let backpack = backpack(player, &items);
+        let item = match chosen_entry(&keys, &backpack) {
+            Some(&(item, _)) => item,
+            None => continue,
+        };
+
+        let action = if *state.current() == RunState::ShowDropItem {
+            commands.insert_one(player, WantsToDropItem { item });
+            Action::HandleItem
+        } else if let Ok(inflicts_damage) = ranged.get(item) {
+            *reticle = Reticle::target(pos, item, inflicts_damage.range);
+            state.set_next(RunState::ShowTargeting).unwrap();
+            return;
+        } else {
+            commands.insert_one(player, WantsToUseItem { item, target: None });
+            Action::UseItem
+        };
+        spend_player_energy(&mut energy, action.cost());
+        state.set_next(RunState::PlayerTurn).unwrap();
+    }
+}
+
+pub fn draw_item_menu_system(
+    mut term: ResMut<Terminal>,
+    map: Res<Map>,
+    state: Res<State<RunState>>,
+    players: Query<Entity, With<Player>>,
+    items: Query<(Entity, &Name, &InBackpack)>,
+) {
+    let title = match state.current() {
+        RunState::ShowInventory => "Use which item? (Esc to cancel)",
+        RunState::ShowDropItem => "Drop which item? (Esc to cancel)",
+        _ => return,
+    };
+
+    for player in players.iter() {
+        let lines = menu_lines(&backpack(player, &items), "You carry nothing.");
+        draw_menu(&mut term, &map, title, &lines);
+    }
+}
+
+/// Lets the player choose an equipped item to put back in the backpack.
+pub fn equipment_menu_input_system(
+    commands: &mut Commands,
+    keys: Res<Input<KeyCode>>,
+    mut state: ResMut<State<RunState>>,
+    players: Query<Entity, With<Player>>,
+    items: Query<(Entity, &Name, &Equipped)>,
+    mut energy: Query<&mut Energy, With<Player>>,
+) {
+    if keys.pressed(KeyCode::Escape) {
+        state.set_next(RunState::AwaitingInput).unwrap();
+        return;
+    }
+
+    for player in players.iter() {
+        if let Some(&(item, _)) = chosen_entry(&keys, &equipment(player, &items)) {
+            commands.insert_one(player, WantsToRemoveItem { item });
+            spend_player_energy(&mut energy, Action::HandleItem.cost());
+            state.set_next(RunState::PlayerTurn).unwrap();
+        }
+    }
+}
+
+pub fn draw_equipment_menu_system(
+    mut term: ResMut<Terminal>,
+    map: Res<Map>,
+    state: Res<State<RunState>>,
+    players: Query<Entity, With<Player>>,
+    items: Query<(Entity, &Name, &Equipped)>,
+) {
+    if *state.current() != RunState::ShowEquipment {
+        return;
+    }
+
+    for player in players.iter() {
+        let lines = menu_lines(&equipment(player, &items), "You have nothing equipped.");
+        draw_menu(
+            &mut term,
+            &map,
+            "Remove which item? (Esc to cancel)",
+            &lines,
+        );
+    }
+}
+
+/// The width the HUD needs to go next to the map rather than below it.
+const SIDE_PANEL_WIDTH: u16 = 30;
+/// The width of the HP bar, in characters.
+const HP_BAR_WIDTH: i32 = 10;
+
+/// The part of the terminal the HUD is drawn in: next to the map if the
+/// terminal is wide enough, below it otherwise. It always reaches the right
+/// edge of the terminal, which clips the lines.
+#[derive(Clone, Copy, Debug)]
+struct Panel {
+    x: u16,
+    y: u16,
+    height: u16,
+}
+
+impl Panel {
+    fn new((width, height): (u16, u16), map: &Map) -> Panel {
+        let map_width = map.width as u16 + 1;
+        if width >= map_width + SIDE_PANEL_WIDTH {
+            Panel {
+                x: map_width,
+                y: 0,
+                height,
+            }
+        } else {
+            let y = (map.height as u16).min(height);
+            Panel {
+                x: 0,
+                y,
+                height: height - y,
+            }
+        }
+    }
+}
+
+fn foreground(color: Color) -> ContentStyle {
+    ContentStyle {
+        foreground_color: Some(color),
+        ..ContentStyle::default()
+    }
+}
+
+/// Green while the player is fine, then yellow, then red.
+fn hp_color(stats: &CombatStats) -> Color {
+    if stats.hp * 2 > stats.max_hp {
+        Color::Green
+    } else if stats.hp * 4 > stats.max_hp {
+        Color::Yellow
+    } else {
+        Color::Red
+    }
+}
+
+/// What currently affects the player, with their colors.
+fn status_effects(map: &Map, pos: &Position, stats: &CombatStats) -> Vec<(&'static str, Color)> {
+    let mut effects = Vec::new();
+    if stats.hp * 2 <= stats.max_hp {
+        effects.push(("Wounded", hp_color(stats)));
+    }
+    if map.tiles[map.xy_idx(pos.x, pos.y)].info().movement_cost > 1 {
+        effects.push(("Slowed", Color::Cyan));
+    }
+    effects
+}
+
+/// Draws the player's HP, the depth, the turn, the seed of the game, the
+/// status effects, what the game waits for, what is under the reticle, and
+/// as much of the log as fits.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn draw_hud_system(
+    mut term: ResMut<Terminal>,
+    map: Res<Map>,
+    seed: Res<Seed>,
+    dungeon: Res<Dungeon>,
+    turn: Res<Turn>,
+    state: Res<State<RunState>>,
+    log: Res<GameLog>,
+    reticle: Res<Reticle>,
+    index: Res<SpatialIndex>,
+    players: Query<(&Position, &CombatStats), With<Player>>,
+    names: Query<(&Name, Option<&CombatStats>)>,
+) {
+    let panel = Panel::new(term.size(), &map);
+    let bottom = panel.y + panel.height;
+    let mut y = panel.y;
+
+    for (pos, stats) in players.iter() {
+        let hp = format!("HP: {}/{} ", stats.hp.max(0), stats.max_hp);
+        let filled = (stats.hp.max(0) * HP_BAR_WIDTH / stats.max_hp.max(1)).min(HP_BAR_WIDTH);
+        let hp_x = panel.x + hp.chars().count() as u16;
+        term.print(panel.x, y, hp);
+        term.print_with_style(
+            hp_x,
+            y,
+            "█".repeat(filled as usize),
+            foreground(hp_color(stats)),
+        );
+        term.print_with_style(
+            hp_x + filled as u16,
+            y,
+            "░".repeat((HP_BAR_WIDTH - filled) as usize),
+            foreground(Color::DarkGrey),
+        );
+        y += 1;
+
+        term.print(
+            panel.x,
+            y,
+            format!("Depth: {}  Turn: {}", dungeon.depth + 1, turn.0),
+        );
+        y += 1;
+        term.print(panel.x, y, format!("Seed: {}", *seed));
+        y += 1;
+
+        let mut x = panel.x;
+        for (effect, color) in status_effects(&map, pos, stats) {
+            term.print_with_style(x, y, effect, foreground(color));
+            x += effect.chars().count() as u16 + 1;
+        }
+        y += 1;
+    }
+
+    if let Some(prompt) = state.current().prompt() {
+        term.print_with_style(panel.x, y, prompt, foreground(Color::Yellow));
+    }
+    y += 1;
+
+    if matches!(state.current(), RunState::Look | RunState::ShowTargeting) {
+        for line in describe_tile(&map, &index, &names, reticle.position) {
+            term.print(panel.x, y, line);
+            y += 1;
+        }
+    }
+    y += 1;
+
+    let rows = bottom.saturating_sub(y) as usize;
+    let entries = log.entries();
+    for entry in &entries[entries.len().saturating_sub(rows)..] {
+        term.print_with_style(panel.x, y, entry.line(), entry.style());
+        y += 1;
+    }
+}
+
+/// What the player knows about a tile: the entities on it if it is in
+/// view, and the tile itself if it was ever seen.
+fn describe_tile(
+    map: &Map,
+    index: &SpatialIndex,
+    names: &Query<(&Name, Option<&CombatStats>)>,
+    (x, y): (i16, i16),
+) -> Vec<String> {
+    let idx = map.xy_idx(x, y);
+    let mut lines = Vec::new();
+    if map.visible_tiles[idx] {
+        for &entity in index.entities_at(x, y) {
+            match names.get(entity) {
+                Ok((name, Some(stats))) => lines.push(format!(
+                    "{} ({}/{} HP)",
+                    name.name,
+                    stats.hp.max(0),
+                    stats.max_hp
+                )),
+                Ok((name, None)) => lines.push(name.name.clone()),
+                Err(_) => {}
+            }
+        }
+    }
+    if map.revealed_tiles[idx] {
+        let info = map.tiles[idx].info();
+        if map.visible_tiles[idx] {
+            lines.push(format!("{}: {}", info.name, info.description));
+        } else {
+            lines.push(format!("{} (remembered)", info.name));
+        }
+    }
+    lines
+}
+
+/// Describes what is under the mouse cursor, in a box next to it, on the
+/// left if there is no room on the right.
+#[allow(clippy::type_complexity)]
+pub fn draw_tooltip_system(
+    mut term: ResMut<Terminal>,
+    cursor: Res<Cursor>,
+    map: Res<Map>,
+    index: Res<SpatialIndex>,
+    state: Res<State<RunState>>,
+    names: Query<(&Name, Option<&CombatStats>)>,
+) {
+    // Only while the map is in plain view: menus and screens are drawn over
+    // it otherwise. The turns are resolved in a frame, so that the tooltip
+    // does not flicker while they are.
+    if !matches!(
+        state.current(),
+        RunState::AwaitingInput
+            | RunState::PlayerTurn
+            | RunState::MonsterTurn
+            | RunState::Look
+            | RunState::ShowTargeting
+    ) {
+        return;
+    }
+    let (x, y) = match cursor.position {
+        Some((x, y)) if x < map.width as u16 && y < map.height as u16 => (x, y),
+        _ => return,
+    };
+
+    let lines = describe_tile(&map, &index, &names, (x as i16, y as i16));
+    if lines.is_empty() {
+        return;
+    }
+    let (term_width, term_height) = term.size();
+    let (width, height) = box_size("", &lines);
+    let box_x = if x + 2 + width <= term_width {
+        x + 2
+    } else {
+        x.saturating_sub(width + 1)
+    };
+    let box_y = y.min(term_height.saturating_sub(height));
+    draw_box(&mut term, box_x, box_y, "", &lines);
+}
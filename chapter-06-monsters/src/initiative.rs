@@ -0,0 +1,68 @@
+use crate::components::{Energy, Player, Speed};
+use bevy::prelude::*;
+
+/// The energy an actor needs to act.
+pub const ENERGY_THRESHOLD: i32 = 100;
+
+/// What an actor can spend its energy on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Steps onto a tile of movement cost `1`. Costlier tiles take
+    /// proportionally more.
+    Move,
+    /// A bit slower than a step, so that fleeing is worth it.
+    Attack,
+    /// Picks up, drops or puts away an item: quicker than a step.
+    HandleItem,
+    /// Drinks, reads, throws or equips an item: slower than a step.
+    UseItem,
+    /// Does nothing for a while.
+    Wait,
+}
+
+impl Action {
+    /// The energy the action costs.
+    pub fn cost(self) -> i32 {
+        match self {
+            Action::Move => 100,
+            Action::Attack => 120,
+            Action::HandleItem => 50,
+            Action::UseItem => 150,
+            Action::Wait => 50,
+        }
+    }
+}
+
+/// Gives energy to all actors until one of them can act.
+///
+/// Only entities of the current level are in the world, so the other
+/// levels stay frozen.
+pub fn initiative_system(mut query: Query<(&Speed, &mut Energy)>) {
+    loop {
+        if query
+            .iter_mut()
+            .any(|(_, energy)| energy.0 >= ENERGY_THRESHOLD)
+        {
+            return;
+        }
+        // Nobody would ever be able to act.
+        if query.iter_mut().all(|(speed, _)| speed.0 <= 0) {
+            return;
+        }
+        for (speed, mut energy) in query.iter_mut() {
+            energy.0 += speed.0;
+        }
+    }
+}
+
+/// Makes the player spend the energy of an action they took.
+pub fn spend_player_energy(query: &mut Query<&mut Energy, With<Player>>, cost: i32) {
+    for mut energy in query.iter_mut() {
+        energy.0 -= cost;
+    }
+}
+
+/// Whether the player has the energy to act.
+pub fn player_is_ready(query: &Query<&Energy, With<Player>>) -> bool {
+    query.iter().any(|energy| energy.0 >= ENERGY_THRESHOLD)
+}
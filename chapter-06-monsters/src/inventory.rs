@@ -0,0 +1,168 @@
+use crate::{
+    combat::Damage,
+    components::{
+        CombatStats, Consumable, Equippable, Equipped, InBackpack, InflictsDamage, Name, Position,
+        ProvidesHealing, WantsToDropItem, WantsToPickupItem, WantsToRemoveItem, WantsToUseItem,
+    },
+    game_log::{name_of, GameLog},
+    map::Map,
+    run_state::Turn,
+    spatial_index::SpatialIndex,
+};
+use bevy::prelude::*;
+use bevy_crossterm::crossterm::style::Color;
+use roguelike_algorithms::{fov::shadow_casting_fov, Point};
+
+// Only the player has a backpack, so all the messages are about them.
+
+pub fn item_collection_system(
+    commands: &mut Commands,
+    mut log: ResMut<GameLog>,
+    turn: Res<Turn>,
+    query: Query<(Entity, &WantsToPickupItem)>,
+    names: Query<&Name>,
+) {
+    for (entity, pickup) in query.iter() {
+        let item = name_of(pickup.item, &names);
+        log.add(turn.0, format!("You pick up the {}.", item), Color::White);
+        commands.remove_one::<WantsToPickupItem>(entity);
+        commands.remove_one::<Position>(pickup.item);
+        commands.insert_one(pickup.item, InBackpack { owner: entity });
+    }
+}
+
+pub fn item_drop_system(
+    commands: &mut Commands,
+    mut log: ResMut<GameLog>,
+    turn: Res<Turn>,
+    query: Query<(Entity, &WantsToDropItem, &Position)>,
+    names: Query<&Name>,
+) {
+    for (entity, drop, pos) in query.iter() {
+        let item = name_of(drop.item, &names);
+        log.add(turn.0, format!("You drop the {}.", item), Color::White);
+        commands.remove_one::<WantsToDropItem>(entity);
+        commands.remove_one::<InBackpack>(drop.item);
+        commands.insert_one(drop.item, pos.clone());
+    }
+}
+
+/// The tiles hit by an item used on `target`: the target itself, or
+/// everything in view of it within `radius`.
+fn area_of_effect(map: &Map, target: Point, radius: i16) -> Vec<Point> {
+    let mut tiles = Vec::new();
+    if radius == 0 {
+        tiles.push(target);
+    } else {
+        shadow_casting_fov(map, target, radius, |point| tiles.push(point));
+    }
+    tiles
+}
+
+pub fn item_remove_system(
+    commands: &mut Commands,
+    mut log: ResMut<GameLog>,
+    turn: Res<Turn>,
+    query: Query<(Entity, &WantsToRemoveItem)>,
+    names: Query<&Name>,
+) {
+    for (entity, remove) in query.iter() {
+        let item = name_of(remove.item, &names);
+        log.add(turn.0, format!("You remove the {}.", item), Color::White);
+        commands.remove_one::<WantsToRemoveItem>(entity);
+        commands.remove_one::<Equipped>(remove.item);
+        commands.insert_one(remove.item, InBackpack { owner: entity });
+    }
+}
+
+/// Heals the user, damages what is on the target, or equips the item,
+/// then uses up the consumables.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn item_use_system(
+    commands: &mut Commands,
+    map: Res<Map>,
+    index: Res<SpatialIndex>,
+    mut damage: ResMut<Events<Damage>>,
+    mut log: ResMut<GameLog>,
+    turn: Res<Turn>,
+    users: Query<(Entity, &WantsToUseItem)>,
+    items: Query<(
+        Option<&Consumable>,
+        Option<&ProvidesHealing>,
+        Option<&InflictsDamage>,
+        Option<&Equippable>,
+    )>,
+    equipped: Query<(Entity, &Equipped)>,
+    mut stats: Query<&mut CombatStats>,
+    names: Query<&Name>,
+) {
+    for (entity, use_item) in users.iter() {
+        commands.remove_one::<WantsToUseItem>(entity);
+        let (consumable, healing, inflicts_damage, equippable) = match items.get(use_item.item) {
+            Ok(item) => item,
+            Err(_) => continue,
+        };
+        let item = name_of(use_item.item, &names);
+
+        if let Some(healing) = healing {
+            if let Ok(mut stats) = stats.get_mut(entity) {
+                stats.hp = (stats.hp + healing.heal_amount).min(stats.max_hp);
+                log.add(
+                    turn.0,
+                    format!("You use the {}, healing {} hp.", item, healing.heal_amount),
+                    Color::Green,
+                );
+            }
+        }
+
+        if let (Some(inflicts_damage), Some((x, y))) = (inflicts_damage, use_item.target) {
+            let mut targets = Vec::new();
+            for point in area_of_effect(&map, Point::new(x, y), inflicts_damage.radius) {
+                for &target in index.entities_at(point.x, point.y) {
+                    if stats.get_mut(target).is_ok() && !targets.contains(&target) {
+                        targets.push(target);
+                    }
+                }
+            }
+            for target in targets {
+                log.add(
+                    turn.0,
+                    format!(
+                        "The {} takes {} damage from the {}.",
+                        name_of(target, &names),
+                        inflicts_damage.damage,
+                        item
+                    ),
+                    Color::Magenta,
+                );
+                damage.send(Damage {
+                    target,
+                    amount: inflicts_damage.damage,
+                });
+            }
+        }
+
+        if let Some(equippable) = equippable {
+            // Only one item per slot: the old one goes back to the backpack.
+            for (other, equipped) in equipped.iter() {
+                if equipped.owner == entity && equipped.slot == equippable.slot {
+                    commands.remove_one::<Equipped>(other);
+                    commands.insert_one(other, InBackpack { owner: entity });
+                }
+            }
+            log.add(turn.0, format!("You equip the {}.", item), Color::White);
+            commands.remove_one::<InBackpack>(use_item.item);
+            commands.insert_one(
+                use_item.item,
+                Equipped {
+                    owner: entity,
+                    slot: equippable.slot,
+                },
+            );
+        }
+
+        if consumable.is_some() {
+            commands.despawn(use_item.item);
+        }
+    }
+}
@@ -0,0 +1,498 @@
+use crate::run_state::RunState;
+use bevy::prelude::*;
+use bevy_crossterm::Terminal;
+use std::{collections::BTreeMap, fmt, fs, io, path::Path, str::FromStr};
+
+/// The letter keys, in order.
+pub const LETTER_KEYS: [KeyCode; 26] = [
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+];
+
+/// The digit keys, in order. Numpad digits are the same keys in a
+/// terminal.
+const DIGIT_KEYS: [KeyCode; 10] = [
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+];
+
+/// The symbol keys, and whether they need Shift, as on a US keyboard.
+const SYMBOL_KEYS: [(char, KeyCode, bool); 18] = [
+    (',', KeyCode::Comma, false),
+    ('.', KeyCode::Period, false),
+    ('/', KeyCode::Slash, false),
+    (';', KeyCode::Semicolon, false),
+    (':', KeyCode::Colon, false),
+    ('\'', KeyCode::Apostrophe, false),
+    ('[', KeyCode::LBracket, false),
+    (']', KeyCode::RBracket, false),
+    ('\\', KeyCode::Backslash, false),
+    ('`', KeyCode::Grave, false),
+    ('@', KeyCode::At, false),
+    ('*', KeyCode::Asterisk, false),
+    ('-', KeyCode::Minus, false),
+    ('=', KeyCode::Equals, false),
+    ('+', KeyCode::Plus, false),
+    ('<', KeyCode::Comma, true),
+    ('>', KeyCode::Period, true),
+    ('?', KeyCode::Slash, true),
+];
+
+/// The symbols that are typed with Shift but have a key code of their own.
+/// Terminals may or may not report Shift with them, so it is ignored.
+const SHIFTED_SYMBOL_KEYS: [KeyCode; 4] = [
+    KeyCode::At,
+    KeyCode::Asterisk,
+    KeyCode::Colon,
+    KeyCode::Plus,
+];
+
+/// The keys written by their names.
+const NAMED_KEYS: [(&str, KeyCode); 14] = [
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Insert", KeyCode::Insert),
+    ("Delete", KeyCode::Delete),
+    ("Space", KeyCode::Space),
+    ("Enter", KeyCode::Return),
+    ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Back),
+];
+
+/// A key, with or without Shift.
+///
+/// Written as the character it types, e.g. `h`, `H` or `>`, or as its
+/// name, e.g. `Left` or `Shift+Tab`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub shift: bool,
+}
+
+impl Key {
+    pub fn pressed(self, keys: &Input<KeyCode>) -> bool {
+        let shift = keys.pressed(KeyCode::LShift) || keys.pressed(KeyCode::RShift);
+        keys.pressed(self.code) && (shift == self.shift || SHIFTED_SYMBOL_KEYS.contains(&self.code))
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, shift) = match s.strip_prefix("Shift+") {
+            Some(name) => (name, true),
+            None => (s, false),
+        };
+        if let Some(&(_, code)) = NAMED_KEYS.iter().find(|&&(key_name, _)| key_name == name) {
+            return Ok(Key { code, shift });
+        }
+
+        let mut chars = name.chars();
+        let c = match (chars.next(), chars.next()) {
+            (Some(c), None) => c,
+            _ => return Err(format!("unknown key: {}", s)),
+        };
+        if c.is_ascii_alphabetic() {
+            let code = LETTER_KEYS[(c.to_ascii_lowercase() as u8 - b'a') as usize];
+            Ok(Key {
+                code,
+                shift: shift || c.is_ascii_uppercase(),
+            })
+        } else if let Some(digit) = c.to_digit(10) {
+            Ok(Key {
+                code: DIGIT_KEYS[digit as usize],
+                shift,
+            })
+        } else if let Some(&(_, code, symbol_shift)) =
+            SYMBOL_KEYS.iter().find(|&&(symbol, _, _)| symbol == c)
+        {
+            Ok(Key {
+                code,
+                // So that `@` and `Shift+@` are the same key.
+                shift: (shift || symbol_shift) && !SHIFTED_SYMBOL_KEYS.contains(&code),
+            })
+        } else {
+            Err(format!("unknown key: {}", s))
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(i) = LETTER_KEYS.iter().position(|&code| code == self.code) {
+            let c = (b'a' + i as u8) as char;
+            if self.shift {
+                write!(f, "{}", c.to_ascii_uppercase())
+            } else {
+                write!(f, "{}", c)
+            }
+        } else if let Some(digit) = DIGIT_KEYS.iter().position(|&code| code == self.code) {
+            write!(f, "{}{}", if self.shift { "Shift+" } else { "" }, digit)
+        } else if let Some(&(symbol, _, _)) = SYMBOL_KEYS
+            .iter()
+            .find(|&&(_, code, shift)| code == self.code && shift == self.shift)
+        {
+            write!(f, "{}", symbol)
+        } else if let Some(&(name, _)) = NAMED_KEYS.iter().find(|&&(_, code)| code == self.code) {
+            write!(f, "{}{}", if self.shift { "Shift+" } else { "" }, name)
+        } else {
+            write!(f, "{:?}", self.code)
+        }
+    }
+}
+
+/// What the player can do with a key while the game waits for them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum InputAction {
+    MoveNorth,
+    MoveSouth,
+    MoveWest,
+    MoveEast,
+    MoveNorthWest,
+    MoveNorthEast,
+    MoveSouthWest,
+    MoveSouthEast,
+    Wait,
+    PickUp,
+    Inventory,
+    Drop,
+    Equipment,
+    Descend,
+    Ascend,
+    Look,
+    Log,
+    Help,
+}
+
+/// The actions, with their names in the config file and their
+/// descriptions on the help screen.
+const ACTIONS: [(InputAction, &str, &str); 18] = [
+    (InputAction::MoveNorth, "move_north", "Move north"),
+    (InputAction::MoveSouth, "move_south", "Move south"),
+    (InputAction::MoveWest, "move_west", "Move west"),
+    (InputAction::MoveEast, "move_east", "Move east"),
+    (
+        InputAction::MoveNorthWest,
+        "move_north_west",
+        "Move north-west",
+    ),
+    (
+        InputAction::MoveNorthEast,
+        "move_north_east",
+        "Move north-east",
+    ),
+    (
+        InputAction::MoveSouthWest,
+        "move_south_west",
+        "Move south-west",
+    ),
+    (
+        InputAction::MoveSouthEast,
+        "move_south_east",
+        "Move south-east",
+    ),
+    (InputAction::Wait, "wait", "Wait a turn"),
+    (InputAction::PickUp, "pick_up", "Pick up an item"),
+    (InputAction::Inventory, "inventory", "Use an item"),
+    (InputAction::Drop, "drop", "Drop an item"),
+    (InputAction::Equipment, "equipment", "Remove equipment"),
+    (InputAction::Descend, "descend", "Go down the stairs"),
+    (InputAction::Ascend, "ascend", "Go up the stairs"),
+    (InputAction::Look, "look", "Look around"),
+    (InputAction::Log, "log", "Read the log"),
+    (InputAction::Help, "help", "Show this help"),
+];
+
+impl InputAction {
+    /// The direction of a movement.
+    pub fn delta(self) -> Option<(i16, i16)> {
+        match self {
+            InputAction::MoveNorth => Some((0, -1)),
+            InputAction::MoveSouth => Some((0, 1)),
+            InputAction::MoveWest => Some((-1, 0)),
+            InputAction::MoveEast => Some((1, 0)),
+            InputAction::MoveNorthWest => Some((-1, -1)),
+            InputAction::MoveNorthEast => Some((1, -1)),
+            InputAction::MoveSouthWest => Some((-1, 1)),
+            InputAction::MoveSouthEast => Some((1, 1)),
+            _ => None,
+        }
+    }
+
+    fn description(self) -> &'static str {
+        ACTIONS
+            .iter()
+            .find(|&&(action, _, _)| action == self)
+            .map_or("", |&(_, _, description)| description)
+    }
+}
+
+impl FromStr for InputAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ACTIONS
+            .iter()
+            .find(|&&(_, name, _)| name == s)
+            .map(|&(action, _, _)| action)
+            .ok_or_else(|| format!("unknown action: {}", s))
+    }
+}
+
+/// A set of key bindings to start from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preset {
+    /// `h/j/k/l` and `y/u/b/n` for the diagonals.
+    Vi,
+    /// The digits around `5`, with NumLock on or off.
+    Numpad,
+    /// `w/a/s/d` and `q/e/z/c` for the diagonals.
+    Wasd,
+}
+
+impl Preset {
+    /// The keys of each action, as written in the config file.
+    fn bindings(self) -> Vec<(InputAction, &'static str)> {
+        let [north, south, west, east, north_west, north_east, south_west, south_east] = match self
+        {
+            Preset::Vi => ["k Up", "j Down", "h Left", "l Right", "y", "u", "b", "n"],
+            Preset::Numpad => [
+                "8 Up",
+                "2 Down",
+                "4 Left",
+                "6 Right",
+                "7 Home",
+                "9 PageUp",
+                "1 End",
+                "3 PageDown",
+            ],
+            Preset::Wasd => ["w Up", "s Down", "a Left", "d Right", "q", "e", "z", "c"],
+        };
+        let (wait, drop, equipment) = match self {
+            Preset::Vi => (". Space", "d", "e"),
+            Preset::Numpad => ("5 . Space", "d", "e"),
+            // `d` and `e` move.
+            Preset::Wasd => (". Space", "r", "f"),
+        };
+
+        vec![
+            (InputAction::MoveNorth, north),
+            (InputAction::MoveSouth, south),
+            (InputAction::MoveWest, west),
+            (InputAction::MoveEast, east),
+            (InputAction::MoveNorthWest, north_west),
+            (InputAction::MoveNorthEast, north_east),
+            (InputAction::MoveSouthWest, south_west),
+            (InputAction::MoveSouthEast, south_east),
+            (InputAction::Wait, wait),
+            (InputAction::PickUp, "g"),
+            (InputAction::Inventory, "i"),
+            (InputAction::Drop, drop),
+            (InputAction::Equipment, equipment),
+            (InputAction::Descend, ">"),
+            (InputAction::Ascend, "<"),
+            (InputAction::Look, "x"),
+            (InputAction::Log, "m"),
+            (InputAction::Help, "?"),
+        ]
+    }
+}
+
+impl FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "vi" => Ok(Preset::Vi),
+            "numpad" => Ok(Preset::Numpad),
+            "wasd" => Ok(Preset::Wasd),
+            _ => Err(format!("unknown key preset: {}", s)),
+        }
+    }
+}
+
+/// The keys of each action.
+///
+/// In the config file, each line binds an action to keys separated by
+/// spaces, and `preset` chooses the keys of the actions not in the file:
+///
+/// ```text
+/// preset = numpad
+/// # Comments start with `#`.
+/// pick_up = g ,
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyBindings {
+    bindings: BTreeMap<InputAction, Vec<Key>>,
+}
+
+impl KeyBindings {
+    pub fn preset(preset: Preset) -> KeyBindings {
+        let bindings = preset
+            .bindings()
+            .into_iter()
+            .map(|(action, keys)| {
+                let keys = keys.split_whitespace().map(|key| key.parse().unwrap());
+                (action, keys.collect())
+            })
+            .collect();
+        KeyBindings { bindings }
+    }
+
+    /// Loads the key bindings from a config file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<KeyBindings> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// The action of the pressed key, if any.
+    pub fn pressed(&self, keys: &Input<KeyCode>) -> Option<InputAction> {
+        self.bindings
+            .iter()
+            .find(|(_, bound)| bound.iter().any(|key| key.pressed(keys)))
+            .map(|(&action, _)| action)
+    }
+
+    /// The direction of the pressed movement key, if any.
+    pub fn movement_delta(&self, keys: &Input<KeyCode>) -> Option<(i16, i16)> {
+        self.pressed(keys).and_then(InputAction::delta)
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings::preset(Preset::Vi)
+    }
+}
+
+impl FromStr for KeyBindings {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut preset = Preset::Vi;
+        let mut rebound = Vec::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |err| format!("line {}: {}", i + 1, err);
+            let (name, keys) = match line.find('=') {
+                Some(eq) => (line[..eq].trim(), line[eq + 1..].trim()),
+                None => return Err(error("expected `action = keys`".to_string())),
+            };
+            if name == "preset" {
+                preset = keys.parse().map_err(error)?;
+            } else {
+                let action: InputAction = name.parse().map_err(error)?;
+                let keys = keys
+                    .split_whitespace()
+                    .map(str::parse)
+                    .collect::<Result<Vec<Key>, _>>()
+                    .map_err(error)?;
+                rebound.push((action, keys));
+            }
+        }
+
+        let mut key_bindings = KeyBindings::preset(preset);
+        key_bindings.bindings.extend(rebound);
+
+        let mut seen: Vec<(Key, InputAction)> = Vec::new();
+        for (&action, keys) in &key_bindings.bindings {
+            for &key in keys {
+                if let Some(&(_, other)) = seen.iter().find(|&&(seen_key, _)| seen_key == key) {
+                    return Err(format!(
+                        "{} is bound to both {} and {}",
+                        key,
+                        other.description(),
+                        action.description()
+                    ));
+                }
+                seen.push((key, action));
+            }
+        }
+        Ok(key_bindings)
+    }
+}
+
+/// Closes the help screen with Esc.
+pub fn help_input_system(keys: Res<Input<KeyCode>>, mut state: ResMut<State<RunState>>) {
+    if keys.pressed(KeyCode::Escape) {
+        state.set_next(RunState::AwaitingInput).unwrap();
+    }
+}
+
+/// Lists the actions and their keys over the screen.
+pub fn draw_help_system(
+    mut term: ResMut<Terminal>,
+    state: Res<State<RunState>>,
+    key_bindings: Res<KeyBindings>,
+) {
+    if *state.current() != RunState::ShowHelp {
+        return;
+    }
+
+    term.cls();
+    term.print(0, 0, "Key bindings");
+    let mut y = 2;
+    for (action, keys) in &key_bindings.bindings {
+        let keys: Vec<_> = keys.iter().map(Key::to_string).collect();
+        term.print(
+            0,
+            y,
+            format!("{:<20}{}", action.description(), keys.join(", ")),
+        );
+        y += 1;
+    }
+    y += 1;
+    for line in &[
+        "Esc: pause menu, or cancel",
+        "Tab: next monster, when looking or targeting",
+        "Enter: confirm the target",
+    ] {
+        term.print(0, y, line);
+        y += 1;
+    }
+    let (_, height) = term.size();
+    term.print(0, height.saturating_sub(1), "Esc to close.");
+}
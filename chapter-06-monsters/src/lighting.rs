@@ -0,0 +1,113 @@
+use crate::map::{Map, TileType};
+use bevy_crossterm::crossterm::style::Color;
+use roguelike_algorithms::{
+    fov::VisibilityGrid,
+    lighting::{Falloff, LightMap, LightSource, Rgb},
+    Point,
+};
+
+/// The light everywhere on the map, even far from any source.
+const AMBIENT: Rgb = Rgb::new(0.05, 0.05, 0.1);
+/// Tiles with a dimmer light can't be seen, even when in view.
+const LIT_THRESHOLD: f32 = 0.1;
+/// The torch carried by the player. It lights less far than the player
+/// could see, so only lit places are seen from further away.
+const TORCH_RADIUS: i16 = 5;
+const TORCH_COLOR: Rgb = Rgb::new(1.0, 0.85, 0.6);
+
+/// The light given off by a tile, if any: its radius and colour.
+fn tile_light(tile: TileType) -> Option<(i16, Rgb)> {
+    match tile {
+        TileType::Lava => Some((2, Rgb::new(0.9, 0.3, 0.1))),
+        _ => None,
+    }
+}
+
+/// The light level of every tile of the current map.
+///
+/// It is recomputed by the visibility system whenever a viewshed changes,
+/// since the player carries a torch.
+pub struct Lighting {
+    width: i16,
+    height: i16,
+    lights: LightMap,
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Lighting {
+            width: 0,
+            height: 0,
+            lights: LightMap::new(0, 0),
+        }
+    }
+}
+
+impl Lighting {
+    /// Relights the map with the player's torch at `player`, if there is a
+    /// player, and the glowing tiles.
+    pub fn update(&mut self, map: &Map, player: Option<Point>) {
+        if (self.width, self.height) != (map.width, map.height) {
+            *self = Lighting {
+                width: map.width,
+                height: map.height,
+                lights: LightMap::new(map.width as usize, map.height as usize),
+            };
+        }
+
+        let torch = player.map(|position| LightSource {
+            position,
+            radius: TORCH_RADIUS,
+            color: TORCH_COLOR,
+            falloff: Falloff::Linear,
+        });
+        let glows = map.tiles.iter().enumerate().filter_map(|(idx, &tile)| {
+            let (x, y) = map.idx_xy(idx);
+            tile_light(tile).map(|(radius, color)| LightSource {
+                position: Point::new(x, y),
+                radius,
+                color,
+                falloff: Falloff::Quadratic,
+            })
+        });
+        let sources: Vec<_> = torch.into_iter().chain(glows).collect();
+
+        self.lights.compute(map, AMBIENT, &sources);
+    }
+
+    /// Removes the tiles too dark to be seen from a field of view.
+    pub fn retain_lit(&self, visible_tiles: &mut VisibilityGrid) {
+        self.lights.retain_lit(visible_tiles, LIT_THRESHOLD);
+    }
+
+    /// Tints `color` by the light at `(x, y)`: half of it keeps its own
+    /// colour, and half takes the colour of the light.
+    pub fn tint(&self, color: Color, x: i16, y: i16) -> Color {
+        let light = self.lights.light(Point::new(x, y));
+        let (r, g, b) = (color_rgb(color) * (light * 0.5 + Rgb::WHITE * 0.5)).to_u8();
+        Color::Rgb { r, g, b }
+    }
+}
+
+/// The usual colour of a terminal colour, white if it can't be told.
+fn color_rgb(color: Color) -> Rgb {
+    match color {
+        Color::Black => Rgb::from_u8(0, 0, 0),
+        Color::DarkGrey => Rgb::from_u8(128, 128, 128),
+        Color::Red => Rgb::from_u8(255, 0, 0),
+        Color::DarkRed => Rgb::from_u8(128, 0, 0),
+        Color::Green => Rgb::from_u8(0, 255, 0),
+        Color::DarkGreen => Rgb::from_u8(0, 128, 0),
+        Color::Yellow => Rgb::from_u8(255, 255, 0),
+        Color::DarkYellow => Rgb::from_u8(128, 128, 0),
+        Color::Blue => Rgb::from_u8(0, 0, 255),
+        Color::DarkBlue => Rgb::from_u8(0, 0, 128),
+        Color::Magenta => Rgb::from_u8(255, 0, 255),
+        Color::DarkMagenta => Rgb::from_u8(128, 0, 128),
+        Color::Cyan => Rgb::from_u8(0, 255, 255),
+        Color::DarkCyan => Rgb::from_u8(0, 128, 128),
+        Color::Grey => Rgb::from_u8(192, 192, 192),
+        Color::Rgb { r, g, b } => Rgb::from_u8(r, g, b),
+        _ => Rgb::WHITE,
+    }
+}
@@ -1,70 +1,101 @@
 use crate::{
-    components::{Monster, Player, Position, Renderable, Viewshed},
+    combat::{damage_system, delete_the_dead_system, melee_combat_system, Damage, COMBAT_STAGE},
+    components::Renderable,
+    dungeon::{change_level_system, ChangeLevel, Dungeon},
+    game_log::{draw_log_viewer_system, log_input_system, GameLog},
+    gui::{
+        draw_equipment_menu_system, draw_hud_system, draw_item_menu_system, draw_tooltip_system,
+        equipment_menu_input_system, item_menu_input_system,
+    },
+    initiative::initiative_system,
+    inventory::{item_collection_system, item_drop_system, item_remove_system, item_use_system},
+    key_bindings::{draw_help_system, help_input_system, KeyBindings, Preset},
+    lighting::Lighting,
     map::{draw_map_system, Map},
+    map_builders::{MapBuilderKind, Prefab, Prefabs},
+    menus::{
+        draw_game_over_system, draw_main_menu_system, draw_pause_menu_system,
+        game_over_input_system, main_menu_input_system, pause_menu_input_system, MainMenu,
+    },
+    monster_ai::monster_ai_system,
+    noise::Noises,
     player::player_input_system,
+    reticle::{
+        draw_reticle_system, look_input_system, start_look_system, targeting_input_system, Reticle,
+    },
+    run_state::{
+        end_monster_turn_system, end_player_turn_system, RunState, Turn, RENDER_STAGE, TURN_STAGE,
+    },
+    save_load::{load_game_system, save_game_system},
+    seed::{PlayedSeed, Seed},
+    spatial_index::{spatial_index_system, SpatialIndex},
     visibility_system::visibility_system,
 };
-use bevy::{input::system::exit_on_esc_system, prelude::*};
+use bevy::prelude::*;
 use bevy_crossterm::{
     crossterm::style::{Color, Colors},
     CrosstermPlugin, Terminal,
 };
-use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand::rngs::StdRng;
 
+mod combat;
 mod components;
+mod dungeon;
+mod game_log;
+mod gui;
+mod initiative;
+mod inventory;
+mod key_bindings;
+mod lighting;
 mod map;
+mod map_builders;
+mod menus;
+mod monster_ai;
+mod noise;
 mod player;
 mod rect;
+mod reticle;
+mod run_state;
+mod save_load;
+mod seed;
+mod spatial_index;
+mod spawner;
 mod visibility_system;
 
-fn spawn_player(commands: &mut Commands, map: Res<Map>) {
-    let (player_x, player_y) = map.rooms[0].center();
-
-    commands.spawn((
-        Position {
-            x: player_x,
-            y: player_y,
-        },
-        Renderable {
-            glyph: '@',
-            fg: Some(Color::Yellow),
-            bg: None,
-        },
-        Player {},
-        Viewshed {
-            visible_tiles: Vec::new(),
-            range: 8,
-            dirty: true,
-        },
-    ));
-}
+/// Starts a new game, replacing whatever was played.
+#[allow(clippy::too_many_arguments)]
+fn new_game_system(
+    commands: &mut Commands,
+    mut state: ResMut<State<RunState>>,
+    kind: Res<MapBuilderKind>,
+    prefabs: Res<Prefabs>,
+    played_seed: Res<PlayedSeed>,
+    mut seed: ResMut<Seed>,
+    mut rng: ResMut<StdRng>,
+    mut turn: ResMut<Turn>,
+    mut map: ResMut<Map>,
+    mut dungeon: ResMut<Dungeon>,
+    mut log: ResMut<GameLog>,
+    entities: Query<Entity>,
+) {
+    spawner::despawn_all(commands, &entities);
 
-fn spawn_monsters(commands: &mut Commands, map: Res<Map>, mut rng: ResMut<StdRng>) {
-    for room in map.rooms.iter().skip(1) {
-        let (x, y) = room.center();
+    *seed = choose_seed();
+    *rng = seed.rng("game");
+    *turn = Turn::default();
+    *map = Map::generate(*seed, *kind, &prefabs, 0);
+    *dungeon = Dungeon::default();
+    *log = GameLog::default();
 
-        let glyph: char;
-        let roll = rng.gen_range(0..2);
-        match roll {
-            1 => glyph = 'g',
-            _ => glyph = 'o',
-        }
+    spawner::player(commands, &map, map.start);
+    let mut rng = seed.rng("monsters 0");
+    spawner::level_monsters(commands, &map, &mut rng);
+    let mut rng = seed.rng("items 0");
+    spawner::level_items(commands, &map, &mut rng);
+    log.add(0, "Welcome to the dungeon!", Color::Cyan);
 
-        commands.spawn((
-            Position { x, y },
-            Renderable {
-                glyph,
-                fg: Some(Color::Red),
-                bg: None,
-            },
-            Monster {},
-            Viewshed {
-                visible_tiles: Vec::new(),
-                range: 8,
-                dirty: true,
-            },
-        ));
-    }
+    played_seed.set(*seed);
+    state.set_next(RunState::AwaitingInput).unwrap();
 }
 
 fn clear_screen_system(mut term: ResMut<Terminal>) {
@@ -74,14 +105,23 @@ fn clear_screen_system(mut term: ResMut<Terminal>) {
 fn render_system(
     mut term: ResMut<Terminal>,
     map: Res<Map>,
-    query: Query<(&Position, &Renderable)>,
+    index: Res<SpatialIndex>,
+    query: Query<&Renderable>,
 ) {
-    for (pos, render) in query.iter() {
-        let idx = map.xy_idx(pos.x, pos.y);
-        if map.visible_tiles[idx] {
+    for (idx, &visible) in map.visible_tiles.iter().enumerate() {
+        if !visible {
+            continue;
+        }
+        let (x, y) = map.idx_xy(idx);
+        let top = index
+            .entities_at(x, y)
+            .iter()
+            .filter_map(|&entity| query.get(entity).ok())
+            .min_by_key(|render| render.render_order);
+        if let Some(render) = top {
             term.put_char_with_color(
-                pos.x as u16,
-                pos.y as u16,
+                x as u16,
+                y as u16,
                 render.glyph,
                 Colors {
                     foreground: render.fg,
@@ -92,20 +132,205 @@ fn render_system(
     }
 }
 
+/// The value following `name` on the command line, e.g. `--seed 42`.
+fn arg_value(name: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != name).nth(1)
+}
+
+/// Reports a bad command line argument and quits, before the terminal is
+/// set up.
+fn usage_error(message: impl std::fmt::Display) -> ! {
+    eprintln!("error: {}", message);
+    std::process::exit(2)
+}
+
+/// Reads the map builder from the `--map <name>` command line argument.
+fn map_builder_kind() -> MapBuilderKind {
+    match arg_value("--map") {
+        Some(name) => name.parse().unwrap_or_else(|err| usage_error(err)),
+        None => MapBuilderKind::Random,
+    }
+}
+
+/// Reads the prefabs from the `.txt` files in the `--prefabs <dir>`
+/// command line argument, or uses the built-in ones.
+fn prefabs() -> Prefabs {
+    match arg_value("--prefabs") {
+        Some(dir) => Prefabs(
+            Prefab::load_dir(&dir).unwrap_or_else(|err| usage_error(format!("{}: {}", dir, err))),
+        ),
+        None => Prefabs::default(),
+    }
+}
+
+/// Reads the key bindings from the `--keys <preset|file>` command line
+/// argument, or uses the vi-keys preset.
+fn key_bindings() -> KeyBindings {
+    match arg_value("--keys") {
+        Some(keys) => match keys.parse::<Preset>() {
+            Ok(preset) => KeyBindings::preset(preset),
+            Err(_) => KeyBindings::load(&keys)
+                .unwrap_or_else(|err| usage_error(format!("{}: {}", keys, err))),
+        },
+        None => KeyBindings::default(),
+    }
+}
+
+/// Reads the seed of a new game from the `--seed <n>` command line
+/// argument, or the `ROGUELIKE_SEED` environment variable. Picks a random
+/// one otherwise.
+fn choose_seed() -> Seed {
+    match arg_value("--seed").or_else(|| std::env::var("ROGUELIKE_SEED").ok()) {
+        Some(seed) => {
+            Seed(seed.parse().unwrap_or_else(|_| {
+                usage_error(format!("the seed should be a u64, not {:?}", seed))
+            }))
+        }
+        None => Seed::random(),
+    }
+}
+
 fn main() {
+    // The command line is checked before the terminal is set up, so that
+    // errors are readable. Until a game starts, the seed, the RNG, the map
+    // and the other resources of a game are only placeholders.
+    let seed = choose_seed();
+    let map_builder_kind = map_builder_kind();
+    let prefabs = prefabs();
+    let key_bindings = key_bindings();
+    let played_seed = PlayedSeed::default();
+
     App::build()
-        .add_resource(Terminal::with_title("Roguelike Tutorial"))
-        .add_resource(StdRng::from_entropy())
+        .add_resource(Terminal::with_title("Roguelike Tutorial").unwrap())
+        .add_resource(seed)
+        .add_resource(seed.rng("game"))
+        .add_resource(played_seed.clone())
+        .add_resource(map_builder_kind)
+        .add_resource(prefabs)
+        .add_resource(key_bindings)
         .init_resource::<Map>()
-        .add_startup_system(spawn_player.system())
-        .add_startup_system(spawn_monsters.system())
-        .add_system(exit_on_esc_system.system())
-        .add_system(player_input_system.system())
-        .add_system(clear_screen_system.system())
-        .add_system(visibility_system.system())
-        .add_system(draw_map_system.system())
-        .add_system(render_system.system())
+        .init_resource::<Dungeon>()
+        .init_resource::<SpatialIndex>()
+        .init_resource::<Lighting>()
+        .init_resource::<Noises>()
+        .init_resource::<Reticle>()
+        .init_resource::<GameLog>()
+        .init_resource::<Turn>()
+        .init_resource::<MainMenu>()
+        .add_event::<ChangeLevel>()
+        .add_event::<Damage>()
+        .add_resource(State::new(RunState::MainMenu))
+        .add_system(spatial_index_system.system())
+        .add_stage_after(stage::UPDATE, TURN_STAGE, StateStage::<RunState>::default())
+        .on_state_update(
+            TURN_STAGE,
+            RunState::MainMenu,
+            main_menu_input_system.system(),
+        )
+        .on_state_update(TURN_STAGE, RunState::NewGame, new_game_system.system())
+        .on_state_update(TURN_STAGE, RunState::LoadGame, load_game_system.system())
+        // Sees what is around the player when a game starts.
+        .on_state_update(
+            TURN_STAGE,
+            RunState::AwaitingInput,
+            visibility_system.system(),
+        )
+        .on_state_update(
+            TURN_STAGE,
+            RunState::AwaitingInput,
+            player_input_system.system(),
+        )
+        .on_state_update(
+            TURN_STAGE,
+            RunState::PlayerTurn,
+            change_level_system.system(),
+        )
+        .on_state_update(TURN_STAGE, RunState::PlayerTurn, visibility_system.system())
+        .on_state_update(
+            TURN_STAGE,
+            RunState::PlayerTurn,
+            end_player_turn_system.system(),
+        )
+        .on_state_update(
+            TURN_STAGE,
+            RunState::MonsterTurn,
+            initiative_system.system(),
+        )
+        .on_state_update(
+            TURN_STAGE,
+            RunState::MonsterTurn,
+            monster_ai_system.system(),
+        )
+        .on_state_update(
+            TURN_STAGE,
+            RunState::MonsterTurn,
+            visibility_system.system(),
+        )
+        .on_state_update(
+            TURN_STAGE,
+            RunState::MonsterTurn,
+            end_monster_turn_system.system(),
+        )
+        .on_state_update(TURN_STAGE, RunState::Menu, pause_menu_input_system.system())
+        .on_state_update(TURN_STAGE, RunState::SaveGame, save_game_system.system())
+        .on_state_update(
+            TURN_STAGE,
+            RunState::ShowInventory,
+            item_menu_input_system.system(),
+        )
+        .on_state_update(
+            TURN_STAGE,
+            RunState::ShowDropItem,
+            item_menu_input_system.system(),
+        )
+        .on_state_update(
+            TURN_STAGE,
+            RunState::ShowEquipment,
+            equipment_menu_input_system.system(),
+        )
+        .on_state_update(TURN_STAGE, RunState::ShowLog, log_input_system.system())
+        .on_state_update(
+            TURN_STAGE,
+            RunState::ShowTargeting,
+            targeting_input_system.system(),
+        )
+        .on_state_enter(TURN_STAGE, RunState::Look, start_look_system.system())
+        .on_state_update(TURN_STAGE, RunState::Look, look_input_system.system())
+        .on_state_update(TURN_STAGE, RunState::ShowHelp, help_input_system.system())
+        .on_state_update(
+            TURN_STAGE,
+            RunState::GameOver,
+            game_over_input_system.system(),
+        )
+        .add_stage_after(TURN_STAGE, COMBAT_STAGE, SystemStage::serial())
+        .add_system_to_stage(COMBAT_STAGE, item_collection_system.system())
+        .add_system_to_stage(COMBAT_STAGE, item_drop_system.system())
+        .add_system_to_stage(COMBAT_STAGE, item_remove_system.system())
+        .add_system_to_stage(COMBAT_STAGE, item_use_system.system())
+        .add_system_to_stage(COMBAT_STAGE, melee_combat_system.system())
+        .add_system_to_stage(COMBAT_STAGE, damage_system.system())
+        .add_system_to_stage(COMBAT_STAGE, delete_the_dead_system.system())
+        .add_stage_after(COMBAT_STAGE, RENDER_STAGE, SystemStage::serial())
+        .add_system_to_stage(RENDER_STAGE, clear_screen_system.system())
+        .add_system_to_stage(RENDER_STAGE, draw_map_system.system())
+        .add_system_to_stage(RENDER_STAGE, render_system.system())
+        .add_system_to_stage(RENDER_STAGE, draw_reticle_system.system())
+        .add_system_to_stage(RENDER_STAGE, draw_item_menu_system.system())
+        .add_system_to_stage(RENDER_STAGE, draw_equipment_menu_system.system())
+        .add_system_to_stage(RENDER_STAGE, draw_hud_system.system())
+        .add_system_to_stage(RENDER_STAGE, draw_tooltip_system.system())
+        .add_system_to_stage(RENDER_STAGE, draw_log_viewer_system.system())
+        .add_system_to_stage(RENDER_STAGE, draw_help_system.system())
+        .add_system_to_stage(RENDER_STAGE, draw_pause_menu_system.system())
+        .add_system_to_stage(RENDER_STAGE, draw_game_over_system.system())
+        .add_system_to_stage(RENDER_STAGE, draw_main_menu_system.system())
         .add_plugins(DefaultPlugins)
         .add_plugin(CrosstermPlugin)
         .run();
+
+    // The terminal has left the alternate screen by now, so this stays
+    // visible after quitting.
+    if let Some(seed) = played_seed.get() {
+        println!("Seed: {}", seed);
+    }
 }
@@ -1,20 +1,219 @@
-use crate::rect::Rect;
+use crate::{
+    lighting::Lighting,
+    map_builders::{
+        place_stairs, post_process, MapBuilder, MapBuilderKind, PrefabBuilder, Prefabs,
+    },
+    rect::Rect,
+    seed::Seed,
+};
 use bevy::prelude::*;
 use bevy_crossterm::{
     crossterm::style::{Color, Colors},
     Terminal,
 };
-use rand::{rngs::StdRng, Rng};
-use roguelike_algorithms::fov::Map2D;
+use roguelike_algorithms::{fov::Map2D, pathfinding::PathMap2D, sound::SoundMap2D, Point};
+use serde::{Deserialize, Serialize};
 use std::cmp::{max, min};
 
-#[derive(PartialEq, Copy, Clone, Debug)]
+/// The size of every generated map. The renderer and the HUD lay
+/// themselves out from the size of the map.
+pub const MAP_WIDTH: i16 = 80;
+pub const MAP_HEIGHT: i16 = 24;
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum TileType {
     Wall,
     Floor,
+    ClosedDoor,
+    OpenDoor,
+    Water,
+    Lava,
+    Glass,
+    Grass,
+    DownStairs,
+    UpStairs,
+    Rubble,
+}
+
+/// The properties of a tile type.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TileInfo {
+    pub name: &'static str,
+    pub glyph: char,
+    /// Blocks the field of view.
+    pub opaque: bool,
+    pub walkable: bool,
+    /// How many turns it takes to step onto the tile. Only meaningful if it
+    /// is walkable.
+    pub movement_cost: u32,
+    /// The damage taken when stepping onto the tile.
+    pub damage: i32,
+    /// The colour when in view.
+    pub lit: Color,
+    /// The colour when revealed but out of view.
+    pub unlit: Color,
+    pub description: &'static str,
+}
+
+impl TileType {
+    pub const ALL: [TileType; 11] = [
+        TileType::Wall,
+        TileType::Floor,
+        TileType::ClosedDoor,
+        TileType::OpenDoor,
+        TileType::Water,
+        TileType::Lava,
+        TileType::Glass,
+        TileType::Grass,
+        TileType::DownStairs,
+        TileType::UpStairs,
+        TileType::Rubble,
+    ];
+
+    pub fn info(self) -> &'static TileInfo {
+        match self {
+            TileType::Wall => &TileInfo {
+                name: "wall",
+                glyph: '#',
+                opaque: true,
+                walkable: false,
+                movement_cost: 1,
+                damage: 0,
+                lit: Color::Green,
+                unlit: Color::AnsiValue(7),
+                description: "A solid stone wall.",
+            },
+            TileType::Floor => &TileInfo {
+                name: "floor",
+                glyph: '.',
+                opaque: false,
+                walkable: true,
+                movement_cost: 1,
+                damage: 0,
+                lit: Color::DarkCyan,
+                unlit: Color::AnsiValue(8),
+                description: "Bare, dusty flagstones.",
+            },
+            TileType::ClosedDoor => &TileInfo {
+                name: "closed door",
+                glyph: '+',
+                opaque: true,
+                walkable: true,
+                movement_cost: 1,
+                damage: 0,
+                lit: Color::DarkYellow,
+                unlit: Color::AnsiValue(7),
+                description: "A heavy wooden door. It opens with a push.",
+            },
+            TileType::OpenDoor => &TileInfo {
+                name: "open door",
+                glyph: '\'',
+                opaque: false,
+                walkable: true,
+                movement_cost: 1,
+                damage: 0,
+                lit: Color::DarkYellow,
+                unlit: Color::AnsiValue(7),
+                description: "A wooden door, standing open.",
+            },
+            TileType::Water => &TileInfo {
+                name: "water",
+                glyph: '~',
+                opaque: false,
+                walkable: true,
+                movement_cost: 2,
+                damage: 0,
+                lit: Color::Blue,
+                unlit: Color::AnsiValue(8),
+                description: "Cold, murky water, waist deep.",
+            },
+            TileType::Lava => &TileInfo {
+                name: "lava",
+                glyph: '=',
+                opaque: false,
+                walkable: true,
+                movement_cost: 2,
+                damage: 4,
+                lit: Color::Red,
+                unlit: Color::AnsiValue(8),
+                description: "Molten rock. It burns whoever steps in it.",
+            },
+            TileType::Glass => &TileInfo {
+                name: "glass wall",
+                glyph: '%',
+                opaque: false,
+                walkable: false,
+                movement_cost: 1,
+                damage: 0,
+                lit: Color::Cyan,
+                unlit: Color::AnsiValue(7),
+                description: "A wall of thick glass. You can see through, but not pass.",
+            },
+            TileType::Grass => &TileInfo {
+                name: "grass",
+                glyph: ',',
+                opaque: false,
+                walkable: true,
+                movement_cost: 1,
+                damage: 0,
+                lit: Color::DarkGreen,
+                unlit: Color::AnsiValue(8),
+                description: "Pale grass, somehow growing without sunlight.",
+            },
+            TileType::DownStairs => &TileInfo {
+                name: "down stairs",
+                glyph: '>',
+                opaque: false,
+                walkable: true,
+                movement_cost: 1,
+                damage: 0,
+                lit: Color::White,
+                unlit: Color::AnsiValue(7),
+                description: "Stairs leading further down.",
+            },
+            TileType::UpStairs => &TileInfo {
+                name: "up stairs",
+                glyph: '<',
+                opaque: false,
+                walkable: true,
+                movement_cost: 1,
+                damage: 0,
+                lit: Color::White,
+                unlit: Color::AnsiValue(7),
+                description: "Stairs leading back up.",
+            },
+            TileType::Rubble => &TileInfo {
+                name: "rubble",
+                glyph: ':',
+                opaque: false,
+                walkable: true,
+                movement_cost: 2,
+                damage: 0,
+                lit: Color::DarkGrey,
+                unlit: Color::AnsiValue(8),
+                description: "Broken stone from a collapsed ceiling.",
+            },
+        }
+    }
+
+    /// The tile type drawn as `glyph`, if any.
+    pub fn from_glyph(glyph: char) -> Option<TileType> {
+        TileType::ALL
+            .iter()
+            .copied()
+            .find(|tile| tile.info().glyph == glyph)
+    }
+
+    pub fn is_walkable(self) -> bool {
+        self.info().walkable
+    }
+
+    pub fn is_opaque(self) -> bool {
+        self.info().opaque
+    }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Map {
     pub tiles: Vec<TileType>,
     pub rooms: Vec<Rect>,
@@ -22,14 +221,39 @@ pub struct Map {
     pub height: i16,
     pub revealed_tiles: Vec<bool>,
     pub visible_tiles: Vec<bool>,
+    pub start: (i16, i16),
+    /// Monsters to spawn, and where, besides the ones in rooms.
+    pub spawns: Vec<((i16, i16), char)>,
 }
 
 impl Map {
+    /// Creates a map filled with walls.
+    pub fn new(width: i16, height: i16) -> Map {
+        let size = width as usize * height as usize;
+        Map {
+            tiles: vec![TileType::Wall; size],
+            rooms: Vec::new(),
+            width,
+            height,
+            revealed_tiles: vec![false; size],
+            visible_tiles: vec![false; size],
+            start: (width / 2, height / 2),
+            spawns: Vec::new(),
+        }
+    }
+
     pub fn xy_idx(&self, x: i16, y: i16) -> usize {
         (y as usize * self.width as usize) + x as usize
     }
 
-    fn apply_room_to_map(&mut self, room: &Rect) {
+    pub fn idx_xy(&self, idx: usize) -> (i16, i16) {
+        (
+            (idx % self.width as usize) as i16,
+            (idx / self.width as usize) as i16,
+        )
+    }
+
+    pub fn apply_room_to_map(&mut self, room: &Rect) {
         for y in room.y1..room.y2 {
             for x in room.x1..room.x2 {
                 let idx = self.xy_idx(x, y);
@@ -38,114 +262,122 @@ impl Map {
         }
     }
 
-    fn apply_horizontal_tunnel(&mut self, x1: i16, x2: i16, y: i16) {
+    pub fn apply_horizontal_tunnel(&mut self, x1: i16, x2: i16, y: i16) {
         for x in min(x1, x2)..=max(x1, x2) {
             let idx = self.xy_idx(x, y);
-            if idx < self.width as usize * self.height as usize {
-                self.tiles[idx as usize] = TileType::Floor;
+            if idx < self.tiles.len() {
+                self.tiles[idx] = TileType::Floor;
             }
         }
     }
 
-    fn apply_vertical_tunnel(&mut self, y1: i16, y2: i16, x: i16) {
+    pub fn apply_vertical_tunnel(&mut self, y1: i16, y2: i16, x: i16) {
         for y in min(y1, y2)..=max(y1, y2) {
             let idx = self.xy_idx(x, y);
-            if idx < self.width as usize * self.height as usize {
-                self.tiles[idx as usize] = TileType::Floor;
+            if idx < self.tiles.len() {
+                self.tiles[idx] = TileType::Floor;
             }
         }
     }
+
+    /// Generates the level at `depth`, `0` being the top one. The same
+    /// seed always gives the same level.
+    pub fn generate(seed: Seed, kind: MapBuilderKind, prefabs: &Prefabs, depth: u32) -> Map {
+        let mut rng = seed.rng(&format!("map {}", depth));
+
+        let builder = PrefabBuilder::new(kind.builder(&mut rng), prefabs.0.clone());
+        let mut map = builder.build_map(MAP_WIDTH, MAP_HEIGHT, &mut rng);
+        post_process(&mut map, &mut rng);
+        place_stairs(&mut map, depth > 0);
+        map
+    }
+
+    /// The position of the first tile of type `tile`, if any.
+    pub fn find_tile(&self, tile: TileType) -> Option<(i16, i16)> {
+        self.tiles
+            .iter()
+            .position(|&t| t == tile)
+            .map(|idx| self.idx_xy(idx))
+    }
+
+    /// The floor tile closest to `(x, y)`, or `(x, y)` itself if there
+    /// is no floor at all.
+    pub fn nearest_floor(&self, x: i16, y: i16) -> (i16, i16) {
+        let target = Point::new(x, y);
+        self.tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, &tile)| tile == TileType::Floor)
+            .map(|(idx, _)| self.idx_xy(idx))
+            .min_by_key(|&(x, y)| target.distance_squared(Point::new(x, y)))
+            .unwrap_or((x, y))
+    }
 }
 
 impl FromResources for Map {
     fn from_resources(resources: &Resources) -> Self {
-        let mut rng = resources.get_mut::<StdRng>().unwrap();
-
-        let mut map = Map {
-            tiles: vec![TileType::Wall; 80 * 24],
-            rooms: Vec::new(),
-            width: 80,
-            height: 24,
-            revealed_tiles: vec![false; 80 * 24],
-            visible_tiles: vec![false; 80 * 24],
-        };
-
-        const MAX_ROOMS: u8 = 30;
-        const MIN_SIZE: i16 = 6;
-        const MAX_SIZE: i16 = 10;
-
-        for _ in 0..MAX_ROOMS {
-            let w = rng.gen_range(MIN_SIZE..MAX_SIZE);
-            let h = rng.gen_range(MIN_SIZE..MAX_SIZE) / 2;
-            let x = rng.gen_range(1..map.width - w - 1);
-            let y = rng.gen_range(1..map.height - h - 1);
-            let new_room = Rect::new(x, y, w, h);
-            let mut ok = true;
-            for other_room in map.rooms.iter() {
-                if new_room.intersect(other_room) {
-                    ok = false
-                }
-            }
-            if ok {
-                map.apply_room_to_map(&new_room);
-
-                if !map.rooms.is_empty() {
-                    let (new_x, new_y) = new_room.center();
-                    let (prev_x, prev_y) = map.rooms[map.rooms.len() - 1].center();
-                    if rng.gen() {
-                        map.apply_horizontal_tunnel(prev_x, new_x, prev_y);
-                        map.apply_vertical_tunnel(prev_y, new_y, new_x);
-                    } else {
-                        map.apply_vertical_tunnel(prev_y, new_y, prev_x);
-                        map.apply_horizontal_tunnel(prev_x, new_x, new_y);
-                    }
-                }
-
-                map.rooms.push(new_room);
-            }
-        }
+        let seed = resources.get::<Seed>().unwrap();
+        let kind = resources
+            .get::<MapBuilderKind>()
+            .map_or(MapBuilderKind::Random, |kind| *kind);
+        let prefabs = resources
+            .get::<Prefabs>()
+            .map_or_else(Prefabs::default, |prefabs| prefabs.clone());
 
-        map
+        Map::generate(*seed, kind, &prefabs, 0)
     }
 }
 
 impl Map2D for Map {
-    fn is_opaque(&self, (x, y): (i16, i16)) -> bool {
+    fn is_opaque(&self, Point { x, y }: Point) -> bool {
         let idx = self.xy_idx(x, y);
-        self.tiles[idx] == TileType::Wall
+        self.tiles[idx].is_opaque()
     }
 
-    fn is_in_bound(&self, (x, y): (i16, i16)) -> bool {
+    fn is_in_bound(&self, Point { x, y }: Point) -> bool {
         x >= 0 && x < self.width && y >= 0 && y < self.height
     }
 }
 
-pub fn draw_map_system(mut term: ResMut<Terminal>, map: Res<Map>) {
+impl SoundMap2D for Map {
+    fn attenuation(&self, point: Point) -> Option<u32> {
+        match self.tiles[self.xy_idx(point.x, point.y)] {
+            // Closed doors muffle sound, rather than block it like walls.
+            TileType::ClosedDoor => Some(4),
+            tile if tile.is_opaque() => None,
+            _ => Some(1),
+        }
+    }
+}
+
+impl PathMap2D for Map {
+    fn cost(&self, point: Point) -> Option<u32> {
+        if !self.is_in_bound(point) {
+            return None;
+        }
+        let info = self.tiles[self.xy_idx(point.x, point.y)].info();
+        // Monsters know better than to step onto harmful tiles.
+        if info.walkable && info.damage == 0 {
+            Some(info.movement_cost)
+        } else {
+            None
+        }
+    }
+}
+
+pub fn draw_map_system(mut term: ResMut<Terminal>, map: Res<Map>, lighting: Res<Lighting>) {
     let mut y = 0;
     let mut x = 0;
     for (idx, tile) in map.tiles.iter().enumerate() {
         // Render a tile depending upon the tile type
         if map.revealed_tiles[idx] {
-            let glyph;
-            let fg;
-            match tile {
-                TileType::Floor => {
-                    glyph = '.';
-                    fg = if map.visible_tiles[idx] {
-                        Color::DarkCyan
-                    } else {
-                        Color::AnsiValue(8)
-                    };
-                }
-                TileType::Wall => {
-                    glyph = '#';
-                    fg = if map.visible_tiles[idx] {
-                        Color::Green
-                    } else {
-                        Color::AnsiValue(7)
-                    };
-                }
-            }
+            let info = tile.info();
+            let glyph = info.glyph;
+            let fg = if map.visible_tiles[idx] {
+                lighting.tint(info.lit, x as i16, y as i16)
+            } else {
+                info.unlit
+            };
             term.put_char_with_color(
                 x,
                 y,
@@ -158,7 +390,7 @@ pub fn draw_map_system(mut term: ResMut<Terminal>, map: Res<Map>) {
         }
 
         x += 1;
-        if x > 79 {
+        if x >= map.width as u16 {
             x = 0;
             y += 1;
         }
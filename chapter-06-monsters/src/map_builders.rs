@@ -0,0 +1,97 @@
+use crate::map::Map;
+use rand::{rngs::StdRng, Rng};
+use std::str::FromStr;
+
+mod bsp_dungeon;
+mod bsp_interior;
+mod cellular_automata;
+mod common;
+mod dla;
+mod drunkard;
+mod maze;
+mod post_processing;
+mod prefab;
+mod simple_map;
+mod voronoi;
+
+pub use bsp_dungeon::BspDungeonBuilder;
+pub use bsp_interior::BspInteriorBuilder;
+pub use cellular_automata::CellularAutomataBuilder;
+pub use dla::DlaBuilder;
+pub use drunkard::DrunkardsWalkBuilder;
+pub use maze::MazeBuilder;
+pub use post_processing::{place_stairs, post_process, reachable_tiles};
+pub use prefab::{Prefab, PrefabBuilder, Prefabs};
+pub use simple_map::SimpleMapBuilder;
+pub use voronoi::VoronoiBuilder;
+
+pub trait MapBuilder {
+    /// Generates a new map. Builders that make rooms also fill in
+    /// `Map::rooms`. `Map::start` is always set to a floor tile.
+    fn build_map(&self, width: i16, height: i16, rng: &mut StdRng) -> Map;
+}
+
+/// Which map builder to use. Insert it as a resource before the `Map` is
+/// created to choose one; otherwise a random one is picked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MapBuilderKind {
+    #[default]
+    Random,
+    Simple,
+    BspDungeon,
+    BspInterior,
+    CellularAutomata,
+    DrunkardsWalk,
+    Dla,
+    Maze,
+    Voronoi,
+}
+
+impl MapBuilderKind {
+    pub const ALL: [MapBuilderKind; 8] = [
+        MapBuilderKind::Simple,
+        MapBuilderKind::BspDungeon,
+        MapBuilderKind::BspInterior,
+        MapBuilderKind::CellularAutomata,
+        MapBuilderKind::DrunkardsWalk,
+        MapBuilderKind::Dla,
+        MapBuilderKind::Maze,
+        MapBuilderKind::Voronoi,
+    ];
+
+    pub fn builder(self, rng: &mut StdRng) -> Box<dyn MapBuilder> {
+        match self {
+            MapBuilderKind::Random => {
+                let kind = MapBuilderKind::ALL[rng.gen_range(0..MapBuilderKind::ALL.len())];
+                kind.builder(rng)
+            }
+            MapBuilderKind::Simple => Box::new(SimpleMapBuilder),
+            MapBuilderKind::BspDungeon => Box::new(BspDungeonBuilder),
+            MapBuilderKind::BspInterior => Box::new(BspInteriorBuilder),
+            MapBuilderKind::CellularAutomata => Box::new(CellularAutomataBuilder),
+            MapBuilderKind::DrunkardsWalk => Box::new(DrunkardsWalkBuilder),
+            MapBuilderKind::Dla => Box::new(DlaBuilder),
+            MapBuilderKind::Maze => Box::new(MazeBuilder),
+            MapBuilderKind::Voronoi => Box::new(VoronoiBuilder),
+        }
+    }
+}
+
+impl FromStr for MapBuilderKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(MapBuilderKind::Random),
+            "simple" => Ok(MapBuilderKind::Simple),
+            "bsp-dungeon" => Ok(MapBuilderKind::BspDungeon),
+            "bsp-interior" => Ok(MapBuilderKind::BspInterior),
+            "cellular-automata" => Ok(MapBuilderKind::CellularAutomata),
+            "drunkards-walk" => Ok(MapBuilderKind::DrunkardsWalk),
+            "dla" => Ok(MapBuilderKind::Dla),
+            "maze" => Ok(MapBuilderKind::Maze),
+            "voronoi" => Ok(MapBuilderKind::Voronoi),
+            _ => Err(format!("unknown map builder: {}", s)),
+        }
+    }
+}
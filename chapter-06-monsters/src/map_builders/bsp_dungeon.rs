@@ -0,0 +1,123 @@
+use crate::{
+    map::{Map, TileType},
+    map_builders::{
+        common::{draw_corridor, set_start},
+        MapBuilder,
+    },
+    rect::Rect,
+};
+use rand::{rngs::StdRng, Rng};
+
+/// Rooms placed in the leaves of a binary space partition, connected in
+/// order from left to right.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BspDungeonBuilder;
+
+impl BspDungeonBuilder {
+    /// Splits `rect` into four quarters.
+    fn add_subrects(rects: &mut Vec<Rect>, rect: &Rect) {
+        let width = rect.x2 - rect.x1;
+        let height = rect.y2 - rect.y1;
+        let half_width = (width / 2).max(1);
+        let half_height = (height / 2).max(1);
+
+        rects.push(Rect::new(rect.x1, rect.y1, half_width, half_height));
+        rects.push(Rect::new(
+            rect.x1,
+            rect.y1 + half_height,
+            half_width,
+            half_height,
+        ));
+        rects.push(Rect::new(
+            rect.x1 + half_width,
+            rect.y1,
+            half_width,
+            half_height,
+        ));
+        rects.push(Rect::new(
+            rect.x1 + half_width,
+            rect.y1 + half_height,
+            half_width,
+            half_height,
+        ));
+    }
+
+    /// A random room inside `rect`.
+    fn random_sub_rect(rect: &Rect, rng: &mut StdRng) -> Rect {
+        let rect_width = (rect.x2 - rect.x1).abs();
+        let rect_height = (rect.y2 - rect.y1).abs();
+
+        let w = rng.gen_range(3..=rect_width.clamp(3, 10));
+        let h = rng.gen_range(2..=rect_height.clamp(2, 6));
+        let x = rect.x1 + rng.gen_range(0..6);
+        let y = rect.y1 + rng.gen_range(0..4);
+
+        Rect::new(x, y, w, h)
+    }
+
+    /// Whether `rect` is inside the map, and surrounded only by walls.
+    fn is_possible(map: &Map, rect: &Rect) -> bool {
+        let expanded = Rect {
+            x1: rect.x1 - 2,
+            x2: rect.x2 + 2,
+            y1: rect.y1 - 2,
+            y2: rect.y2 + 2,
+        };
+
+        for y in expanded.y1..=expanded.y2 {
+            for x in expanded.x1..=expanded.x2 {
+                if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 {
+                    return false;
+                }
+                let idx = map.xy_idx(x, y);
+                if map.tiles[idx] != TileType::Wall {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+impl MapBuilder for BspDungeonBuilder {
+    fn build_map(&self, width: i16, height: i16, rng: &mut StdRng) -> Map {
+        let mut map = Map::new(width, height);
+
+        const MAX_TRIES: u16 = 240;
+
+        let mut rects = Vec::new();
+        let first = Rect::new(2, 2, width - 5, height - 5);
+        BspDungeonBuilder::add_subrects(&mut rects, &first);
+
+        for _ in 0..MAX_TRIES {
+            let rect = rects[rng.gen_range(0..rects.len())].clone();
+            let candidate = BspDungeonBuilder::random_sub_rect(&rect, rng);
+
+            if BspDungeonBuilder::is_possible(&map, &candidate) {
+                map.apply_room_to_map(&candidate);
+                map.rooms.push(candidate);
+                BspDungeonBuilder::add_subrects(&mut rects, &rect);
+            }
+        }
+
+        map.rooms.sort_by_key(|room| room.x1);
+
+        for i in 1..map.rooms.len() {
+            let room = &map.rooms[i - 1];
+            let next_room = &map.rooms[i];
+            let start = (
+                rng.gen_range(room.x1..room.x2),
+                rng.gen_range(room.y1..room.y2),
+            );
+            let end = (
+                rng.gen_range(next_room.x1..next_room.x2),
+                rng.gen_range(next_room.y1..next_room.y2),
+            );
+            draw_corridor(&mut map, start, end);
+        }
+
+        set_start(&mut map);
+        map
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{
+    map::Map,
+    map_builders::{
+        common::{draw_corridor, set_start},
+        MapBuilder,
+    },
+    rect::Rect,
+};
+use rand::{rngs::StdRng, Rng};
+
+/// The whole map divided into rooms by a binary space partition, like
+/// the interior of a building.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BspInteriorBuilder;
+
+const MIN_ROOM_WIDTH: i16 = 8;
+const MIN_ROOM_HEIGHT: i16 = 4;
+
+impl BspInteriorBuilder {
+    /// Splits `rect` in two, leaving a wall between the halves, until the
+    /// pieces are too small.
+    fn split(rooms: &mut Vec<Rect>, rect: Rect, rng: &mut StdRng) {
+        let width = rect.x2 - rect.x1;
+        let height = rect.y2 - rect.y1;
+
+        let can_split_x = width > MIN_ROOM_WIDTH * 2;
+        let can_split_y = height > MIN_ROOM_HEIGHT * 2;
+
+        let split_x = match (can_split_x, can_split_y) {
+            (false, false) => {
+                rooms.push(rect);
+                return;
+            }
+            (true, false) => true,
+            (false, true) => false,
+            (true, true) => rng.gen(),
+        };
+
+        if split_x {
+            let left = rng.gen_range(MIN_ROOM_WIDTH..=width - MIN_ROOM_WIDTH - 1);
+            BspInteriorBuilder::split(rooms, Rect::new(rect.x1, rect.y1, left, height), rng);
+            BspInteriorBuilder::split(
+                rooms,
+                Rect::new(rect.x1 + left + 1, rect.y1, width - left - 1, height),
+                rng,
+            );
+        } else {
+            let top = rng.gen_range(MIN_ROOM_HEIGHT..=height - MIN_ROOM_HEIGHT - 1);
+            BspInteriorBuilder::split(rooms, Rect::new(rect.x1, rect.y1, width, top), rng);
+            BspInteriorBuilder::split(
+                rooms,
+                Rect::new(rect.x1, rect.y1 + top + 1, width, height - top - 1),
+                rng,
+            );
+        }
+    }
+}
+
+impl MapBuilder for BspInteriorBuilder {
+    fn build_map(&self, width: i16, height: i16, rng: &mut StdRng) -> Map {
+        let mut map = Map::new(width, height);
+
+        let mut rooms = Vec::new();
+        BspInteriorBuilder::split(&mut rooms, Rect::new(1, 1, width - 2, height - 2), rng);
+
+        for room in rooms.iter() {
+            map.apply_room_to_map(room);
+        }
+
+        for i in 1..rooms.len() {
+            draw_corridor(&mut map, rooms[i - 1].center(), rooms[i].center());
+        }
+
+        map.rooms = rooms;
+        set_start(&mut map);
+        map
+    }
+}
@@ -0,0 +1,58 @@
+use crate::{
+    map::{Map, TileType},
+    map_builders::{common::set_start, MapBuilder},
+};
+use rand::{rngs::StdRng, Rng};
+
+/// Natural-looking caves, grown from random noise by a cellular automaton.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CellularAutomataBuilder;
+
+impl MapBuilder for CellularAutomataBuilder {
+    fn build_map(&self, width: i16, height: i16, rng: &mut StdRng) -> Map {
+        let mut map = Map::new(width, height);
+
+        const FLOOR_PERCENT: u8 = 55;
+        const ITERATIONS: u8 = 15;
+
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                let idx = map.xy_idx(x, y);
+                if rng.gen_range(0..100) < FLOOR_PERCENT {
+                    map.tiles[idx] = TileType::Floor;
+                }
+            }
+        }
+
+        for _ in 0..ITERATIONS {
+            let mut new_tiles = map.tiles.clone();
+
+            for y in 1..height - 1 {
+                for x in 1..width - 1 {
+                    let mut neighbours = 0;
+                    for dy in -1..=1 {
+                        for dx in -1..=1 {
+                            if (dx, dy) != (0, 0)
+                                && map.tiles[map.xy_idx(x + dx, y + dy)] == TileType::Wall
+                            {
+                                neighbours += 1;
+                            }
+                        }
+                    }
+
+                    let idx = map.xy_idx(x, y);
+                    new_tiles[idx] = if neighbours > 4 || neighbours == 0 {
+                        TileType::Wall
+                    } else {
+                        TileType::Floor
+                    };
+                }
+            }
+
+            map.tiles = new_tiles;
+        }
+
+        set_start(&mut map);
+        map
+    }
+}
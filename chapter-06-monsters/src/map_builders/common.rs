@@ -0,0 +1,40 @@
+use crate::map::{Map, TileType};
+use std::cmp::Ordering;
+
+/// Carves a corridor that goes straight horizontally, then vertically.
+pub fn draw_corridor(map: &mut Map, (x1, y1): (i16, i16), (x2, y2): (i16, i16)) {
+    let (mut x, mut y) = (x1, y1);
+
+    while x != x2 || y != y2 {
+        match x.cmp(&x2) {
+            Ordering::Less => x += 1,
+            Ordering::Greater => x -= 1,
+            Ordering::Equal => {
+                if y < y2 {
+                    y += 1;
+                } else {
+                    y -= 1;
+                }
+            }
+        }
+
+        let idx = map.xy_idx(x, y);
+        map.tiles[idx] = TileType::Floor;
+    }
+}
+
+pub fn count_floor(map: &Map) -> usize {
+    map.tiles
+        .iter()
+        .filter(|&&tile| tile == TileType::Floor)
+        .count()
+}
+
+/// Starts in the first room if there is one, otherwise on the floor tile
+/// closest to the center of the map.
+pub fn set_start(map: &mut Map) {
+    map.start = match map.rooms.first() {
+        Some(room) => room.center(),
+        None => map.nearest_floor(map.width / 2, map.height / 2),
+    };
+}
@@ -0,0 +1,55 @@
+use crate::{
+    map::{Map, TileType},
+    map_builders::{
+        common::{count_floor, set_start},
+        MapBuilder,
+    },
+};
+use rand::{rngs::StdRng, Rng};
+
+/// Diffusion-limited aggregation: diggers walk in from random places
+/// until they bump into the existing caves, and stick to them.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DlaBuilder;
+
+impl MapBuilder for DlaBuilder {
+    fn build_map(&self, width: i16, height: i16, rng: &mut StdRng) -> Map {
+        let mut map = Map::new(width, height);
+
+        const FLOOR_PERCENT: usize = 25;
+
+        // A small cross as the seed.
+        let (start_x, start_y) = (width / 2, height / 2);
+        for &(dx, dy) in [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)].iter() {
+            let idx = map.xy_idx(start_x + dx, start_y + dy);
+            map.tiles[idx] = TileType::Floor;
+        }
+
+        let desired_floor = map.tiles.len() * FLOOR_PERCENT / 100;
+
+        while count_floor(&map) < desired_floor {
+            let mut x = rng.gen_range(1..width - 1);
+            let mut y = rng.gen_range(1..height - 1);
+            let (mut prev_x, mut prev_y) = (x, y);
+
+            while map.tiles[map.xy_idx(x, y)] == TileType::Wall {
+                prev_x = x;
+                prev_y = y;
+
+                match rng.gen_range(0..4) {
+                    0 if x > 2 => x -= 1,
+                    1 if x < width - 3 => x += 1,
+                    2 if y > 2 => y -= 1,
+                    3 if y < height - 3 => y += 1,
+                    _ => {}
+                }
+            }
+
+            let idx = map.xy_idx(prev_x, prev_y);
+            map.tiles[idx] = TileType::Floor;
+        }
+
+        set_start(&mut map);
+        map
+    }
+}
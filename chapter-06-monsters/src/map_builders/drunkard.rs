@@ -0,0 +1,49 @@
+use crate::{
+    map::{Map, TileType},
+    map_builders::{
+        common::{count_floor, set_start},
+        MapBuilder,
+    },
+};
+use rand::{rngs::StdRng, Rng};
+
+/// Caves carved by diggers stumbling around at random.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DrunkardsWalkBuilder;
+
+impl MapBuilder for DrunkardsWalkBuilder {
+    fn build_map(&self, width: i16, height: i16, rng: &mut StdRng) -> Map {
+        let mut map = Map::new(width, height);
+
+        const FLOOR_PERCENT: usize = 50;
+        const LIFETIME: u16 = 400;
+
+        let (start_x, start_y) = (width / 2, height / 2);
+        let start_idx = map.xy_idx(start_x, start_y);
+        map.tiles[start_idx] = TileType::Floor;
+
+        let desired_floor = map.tiles.len() * FLOOR_PERCENT / 100;
+
+        while count_floor(&map) < desired_floor {
+            // Every digger starts from the middle, so the caves are
+            // connected.
+            let (mut x, mut y) = (start_x, start_y);
+
+            for _ in 0..LIFETIME {
+                let idx = map.xy_idx(x, y);
+                map.tiles[idx] = TileType::Floor;
+
+                match rng.gen_range(0..4) {
+                    0 if x > 2 => x -= 1,
+                    1 if x < width - 3 => x += 1,
+                    2 if y > 2 => y -= 1,
+                    3 if y < height - 3 => y += 1,
+                    _ => {}
+                }
+            }
+        }
+
+        set_start(&mut map);
+        map
+    }
+}
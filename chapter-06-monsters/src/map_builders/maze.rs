@@ -0,0 +1,55 @@
+use crate::{
+    map::{Map, TileType},
+    map_builders::{common::set_start, MapBuilder},
+};
+use rand::{rngs::StdRng, seq::SliceRandom};
+
+/// A perfect maze, generated by a recursive backtracker.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MazeBuilder;
+
+impl MapBuilder for MazeBuilder {
+    fn build_map(&self, width: i16, height: i16, rng: &mut StdRng) -> Map {
+        let mut map = Map::new(width, height);
+
+        // Cell `(x, y)` of the maze is tile `(2 * x + 1, 2 * y + 1)`, and
+        // the tiles between cells are walls or passages.
+        let columns = (width - 1) / 2;
+        let rows = (height - 1) / 2;
+        let mut visited = vec![false; columns as usize * rows as usize];
+        let cell_idx = |x: i16, y: i16| (y * columns + x) as usize;
+
+        let mut stack = vec![(0, 0)];
+        visited[0] = true;
+        let idx = map.xy_idx(1, 1);
+        map.tiles[idx] = TileType::Floor;
+
+        while let Some(&(x, y)) = stack.last() {
+            let mut neighbours: Vec<(i16, i16)> = [(0, -1), (1, 0), (0, 1), (-1, 0)]
+                .iter()
+                .map(|&(dx, dy)| (x + dx, y + dy))
+                .filter(|&(nx, ny)| {
+                    nx >= 0 && nx < columns && ny >= 0 && ny < rows && !visited[cell_idx(nx, ny)]
+                })
+                .collect();
+            neighbours.shuffle(rng);
+
+            match neighbours.first() {
+                Some(&(nx, ny)) => {
+                    visited[cell_idx(nx, ny)] = true;
+                    let wall = map.xy_idx(x + nx + 1, y + ny + 1);
+                    map.tiles[wall] = TileType::Floor;
+                    let cell = map.xy_idx(nx * 2 + 1, ny * 2 + 1);
+                    map.tiles[cell] = TileType::Floor;
+                    stack.push((nx, ny));
+                }
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+
+        set_start(&mut map);
+        map
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{
+    map::{Map, TileType},
+    map_builders::common::draw_corridor,
+    rect::Rect,
+};
+use rand::{rngs::StdRng, Rng};
+use roguelike_algorithms::Point;
+use std::collections::VecDeque;
+
+/// Post-processing passes, in order, applied to every generated map.
+pub fn post_process(map: &mut Map, rng: &mut StdRng) {
+    add_loops(map, rng);
+    cull_unreachable(map);
+    ensure_room(map);
+    place_doors(map);
+
+    assert!(
+        reachable_tiles(map)
+            .iter()
+            .zip(map.tiles.iter())
+            .all(|(&reachable, tile)| reachable || !tile.is_walkable()),
+        "some floor tiles are unreachable from the start"
+    );
+}
+
+/// Which tiles can be walked to from `map.start`, through orthogonal
+/// steps.
+pub fn reachable_tiles(map: &Map) -> Vec<bool> {
+    let mut reachable = vec![false; map.tiles.len()];
+    let (start_x, start_y) = map.start;
+    let start_idx = map.xy_idx(start_x, start_y);
+    if !map.tiles[start_idx].is_walkable() {
+        return reachable;
+    }
+
+    reachable[start_idx] = true;
+    let mut stack = vec![Point::new(start_x, start_y)];
+
+    while let Some(point) = stack.pop() {
+        for neighbour in point.neighbours_4() {
+            if neighbour.x < 0
+                || neighbour.x >= map.width
+                || neighbour.y < 0
+                || neighbour.y >= map.height
+            {
+                continue;
+            }
+            let idx = map.xy_idx(neighbour.x, neighbour.y);
+            if !reachable[idx] && map.tiles[idx].is_walkable() {
+                reachable[idx] = true;
+                stack.push(neighbour);
+            }
+        }
+    }
+
+    reachable
+}
+
+/// Puts down stairs on the floor tile farthest from the start, and up
+/// stairs on the start if `up_stairs` is true.
+pub fn place_stairs(map: &mut Map, up_stairs: bool) {
+    let mut distances = vec![None; map.tiles.len()];
+    let (start_x, start_y) = map.start;
+    let start_idx = map.xy_idx(start_x, start_y);
+    distances[start_idx] = Some(0);
+    let mut queue = VecDeque::new();
+    queue.push_back(Point::new(start_x, start_y));
+
+    while let Some(point) = queue.pop_front() {
+        let distance = distances[map.xy_idx(point.x, point.y)].unwrap_or(0);
+        for neighbour in point.neighbours_4() {
+            if neighbour.x < 0
+                || neighbour.x >= map.width
+                || neighbour.y < 0
+                || neighbour.y >= map.height
+            {
+                continue;
+            }
+            let idx = map.xy_idx(neighbour.x, neighbour.y);
+            if distances[idx].is_none() && map.tiles[idx].is_walkable() {
+                distances[idx] = Some(distance + 1);
+                queue.push_back(neighbour);
+            }
+        }
+    }
+
+    let farthest = distances
+        .iter()
+        .enumerate()
+        .filter(|&(idx, _)| idx != start_idx && map.tiles[idx] == TileType::Floor)
+        .filter_map(|(idx, distance)| distance.map(|distance| (idx, distance)))
+        .max_by_key(|&(_, distance)| distance);
+    if let Some((idx, _)) = farthest {
+        map.tiles[idx] = TileType::DownStairs;
+    }
+
+    if up_stairs {
+        map.tiles[start_idx] = TileType::UpStairs;
+    }
+}
+
+/// Connects each room to its nearest other room, besides the corridors
+/// the builder made, so that the dungeon has some loops.
+fn add_loops(map: &mut Map, rng: &mut StdRng) {
+    const LOOP_CHANCE: f64 = 0.3;
+
+    for i in 0..map.rooms.len() {
+        if !rng.gen_bool(LOOP_CHANCE) {
+            continue;
+        }
+
+        let (x, y) = map.rooms[i].center();
+        let nearest = map
+            .rooms
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .map(|(_, room)| room.center())
+            .min_by_key(|&(other_x, other_y)| {
+                Point::new(x, y).distance_squared(Point::new(other_x, other_y))
+            });
+
+        if let Some(end) = nearest {
+            draw_corridor(map, (x, y), end);
+        }
+    }
+}
+
+/// Turns walkable tiles that can't be reached from the start into walls,
+/// and forgets rooms whose center was culled, and spawns that were culled.
+/// Terrain that can't be walked on, e.g. glass, is left alone.
+fn cull_unreachable(map: &mut Map) {
+    let (start_x, start_y) = map.start;
+    let start_idx = map.xy_idx(start_x, start_y);
+    map.tiles[start_idx] = TileType::Floor;
+
+    let reachable = reachable_tiles(map);
+    for (tile, &reachable) in map.tiles.iter_mut().zip(reachable.iter()) {
+        if tile.is_walkable() && !reachable {
+            *tile = TileType::Wall;
+        }
+    }
+
+    let rooms = std::mem::take(&mut map.rooms);
+    map.rooms = rooms
+        .into_iter()
+        .filter(|room| {
+            let (x, y) = room.center();
+            reachable[map.xy_idx(x, y)]
+        })
+        .collect();
+
+    let spawns = std::mem::take(&mut map.spawns);
+    map.spawns = spawns
+        .into_iter()
+        .filter(|&((x, y), _)| reachable[map.xy_idx(x, y)])
+        .collect();
+}
+
+/// Makes sure there is at least one room, carving a small one around the
+/// start for maps without rooms.
+fn ensure_room(map: &mut Map) {
+    if !map.rooms.is_empty() {
+        return;
+    }
+
+    let (x, y) = map.start;
+    let x1 = (x - 2).max(1);
+    let y1 = (y - 1).max(1);
+    let x2 = (x + 3).min(map.width - 1);
+    let y2 = (y + 2).min(map.height - 1);
+    let room = Rect { x1, y1, x2, y2 };
+
+    map.apply_room_to_map(&room);
+    map.rooms.push(room);
+}
+
+/// Puts doors where corridors enter rooms, i.e., on floor tiles just
+/// outside a room with walls on both sides along its edge.
+fn place_doors(map: &mut Map) {
+    let mut candidates = Vec::new();
+
+    for room in map.rooms.iter() {
+        let Rect { x1, y1, x2, y2 } = *room;
+
+        for x in x1..x2 {
+            candidates.push((x, y1 - 1, (1, 0)));
+            candidates.push((x, y2, (1, 0)));
+        }
+        for y in y1..y2 {
+            candidates.push((x1 - 1, y, (0, 1)));
+            candidates.push((x2, y, (0, 1)));
+        }
+    }
+
+    for (x, y, along) in candidates {
+        if is_doorway(map, x, y, along) {
+            let idx = map.xy_idx(x, y);
+            map.tiles[idx] = TileType::ClosedDoor;
+        }
+    }
+}
+
+/// Whether `(x, y)` is a floor tile, with walls on both sides along
+/// `(dx, dy)` and no door next to it.
+fn is_doorway(map: &Map, x: i16, y: i16, (dx, dy): (i16, i16)) -> bool {
+    if x < 1 || x >= map.width - 1 || y < 1 || y >= map.height - 1 {
+        return false;
+    }
+    let tile = |x, y| map.tiles[map.xy_idx(x, y)];
+
+    tile(x, y) == TileType::Floor
+        && tile(x - dx, y - dy) == TileType::Wall
+        && tile(x + dx, y + dy) == TileType::Wall
+        && Point::new(x, y)
+            .neighbours_8()
+            .all(|p| !matches!(tile(p.x, p.y), TileType::ClosedDoor | TileType::OpenDoor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_builders::Prefab;
+    use rand::SeedableRng;
+
+    #[test]
+    fn placed_glass_prefab_survives_post_processing() {
+        let prefab: Prefab = include_str!("../../prefabs/flooded_shrine.txt")
+            .parse()
+            .unwrap();
+        let mut map = Map::new(30, 12);
+        prefab.apply_to_map(&mut map, 1, 1);
+        let room = Rect::new(15, 2, 8, 5);
+        let start = room.center();
+        map.apply_room_to_map(&room);
+        map.rooms.push(room);
+        map.start = start;
+        // From the room to below the door of the prefab.
+        draw_corridor(&mut map, start, (6, 8));
+        let before = map.tiles.clone();
+
+        post_process(&mut map, &mut StdRng::seed_from_u64(0));
+
+        for (idx, &tile) in before.iter().enumerate() {
+            if tile == TileType::Glass {
+                assert_eq!(
+                    map.tiles[idx],
+                    TileType::Glass,
+                    "glass at {:?}",
+                    map.idx_xy(idx)
+                );
+            }
+        }
+        assert!(map.spawns.contains(&((6, 4), 'g')));
+    }
+}
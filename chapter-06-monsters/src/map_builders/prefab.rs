@@ -0,0 +1,272 @@
+use crate::{
+    map::{Map, TileType},
+    map_builders::{common::draw_corridor, reachable_tiles, MapBuilder},
+    rect::Rect,
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+use roguelike_algorithms::{geometry::Octant, Point};
+use std::{fs, io, path::Path, str::FromStr};
+
+/// A cell of a prefab.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrefabCell {
+    /// A space: keeps whatever the map already has.
+    Keep,
+    /// The glyph of a tile type, e.g. `#` for a wall.
+    Tile(TileType),
+    /// A letter: a floor tile with a monster spawn, e.g. `g` for a goblin.
+    Spawn(char),
+}
+
+impl PrefabCell {
+    fn from_char(c: char) -> Result<PrefabCell, String> {
+        match c {
+            ' ' => Ok(PrefabCell::Keep),
+            c if c.is_ascii_alphabetic() => Ok(PrefabCell::Spawn(c)),
+            c => TileType::from_glyph(c)
+                .map(PrefabCell::Tile)
+                .ok_or_else(|| format!("unknown prefab cell: {:?}", c)),
+        }
+    }
+}
+
+/// A hand-designed room, written as ASCII art.
+///
+/// Each tile is written as its glyph, e.g. `#` for a wall or `~` for
+/// water, and a letter is a monster spawn on the floor, e.g. `g` for a
+/// goblin or `o` for an orc. Spaces keep the tiles of the
+/// map underneath, so prefabs don't have to be rectangular.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Prefab {
+    pub width: i16,
+    pub height: i16,
+    cells: Vec<PrefabCell>,
+}
+
+impl Prefab {
+    /// The prefabs that come with the game.
+    pub fn builtin() -> Vec<Prefab> {
+        [
+            include_str!("../../prefabs/goblin_den.txt"),
+            include_str!("../../prefabs/guard_post.txt"),
+            include_str!("../../prefabs/pillared_hall.txt"),
+            include_str!("../../prefabs/orc_barracks.txt"),
+            include_str!("../../prefabs/flooded_shrine.txt"),
+        ]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect()
+    }
+
+    /// Loads a prefab from a text file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Prefab> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Loads all `.txt` files in a directory as prefabs.
+    pub fn load_dir<P: AsRef<Path>>(dir: P) -> io::Result<Vec<Prefab>> {
+        let mut paths = fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        // `read_dir` has no particular order, but the same seed should give
+        // the same map.
+        paths.sort();
+        paths
+            .iter()
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("txt"))
+            .map(Prefab::load)
+            .collect()
+    }
+
+    pub fn cell(&self, x: i16, y: i16) -> PrefabCell {
+        self.cells[y as usize * self.width as usize + x as usize]
+    }
+
+    /// Rotates or mirrors the prefab, the way `octant` transforms an offset
+    /// in octant 0. The eight octants give all eight orientations.
+    pub fn transformed(&self, octant: Octant) -> Prefab {
+        let offsets: Vec<_> = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| Point::new(x, y)))
+            .map(|p| octant.from_octant0_offset(p))
+            .collect();
+        let min_x = offsets.iter().map(|p| p.x).min().unwrap_or(0);
+        let min_y = offsets.iter().map(|p| p.y).min().unwrap_or(0);
+        let max_x = offsets.iter().map(|p| p.x).max().unwrap_or(-1);
+        let max_y = offsets.iter().map(|p| p.y).max().unwrap_or(-1);
+
+        let width = max_x - min_x + 1;
+        let height = max_y - min_y + 1;
+        let mut cells = vec![PrefabCell::Keep; width as usize * height as usize];
+        for (offset, &cell) in offsets.iter().zip(self.cells.iter()) {
+            let x = offset.x - min_x;
+            let y = offset.y - min_y;
+            cells[y as usize * width as usize + x as usize] = cell;
+        }
+
+        Prefab {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Stamps the prefab into the map with its top left corner at `(x, y)`.
+    pub fn apply_to_map(&self, map: &mut Map, x: i16, y: i16) {
+        for dy in 0..self.height {
+            for dx in 0..self.width {
+                let idx = map.xy_idx(x + dx, y + dy);
+                match self.cell(dx, dy) {
+                    PrefabCell::Keep => {}
+                    PrefabCell::Tile(tile) => map.tiles[idx] = tile,
+                    PrefabCell::Spawn(glyph) => {
+                        map.tiles[idx] = TileType::Floor;
+                        map.spawns.push(((x + dx, y + dy), glyph));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for Prefab {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<&str> = s.trim_end_matches(&['\r', '\n'][..]).lines().collect();
+        let height = lines.len();
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        if width == 0 {
+            return Err("empty prefab".to_string());
+        }
+
+        let mut cells = Vec::with_capacity(width * height);
+        for line in lines {
+            for c in line.chars() {
+                cells.push(PrefabCell::from_char(c)?);
+            }
+            // Short lines are padded with spaces.
+            for _ in line.chars().count()..width {
+                cells.push(PrefabCell::Keep);
+            }
+        }
+
+        Ok(Prefab {
+            width: width as i16,
+            height: height as i16,
+            cells,
+        })
+    }
+}
+
+/// The prefabs to place into generated maps. Insert it as a resource
+/// before the `Map` is created to replace the built-in ones.
+#[derive(Clone, Debug)]
+pub struct Prefabs(pub Vec<Prefab>);
+
+impl Default for Prefabs {
+    fn default() -> Self {
+        Prefabs(Prefab::builtin())
+    }
+}
+
+/// Builds a map with another builder, then stamps a few prefabs into it
+/// where they don't overlap any room, each connected to the rest of the
+/// map by a corridor.
+pub struct PrefabBuilder {
+    pub base: Box<dyn MapBuilder>,
+    pub prefabs: Vec<Prefab>,
+}
+
+impl PrefabBuilder {
+    pub fn new(base: Box<dyn MapBuilder>, prefabs: Vec<Prefab>) -> PrefabBuilder {
+        PrefabBuilder { base, prefabs }
+    }
+}
+
+impl MapBuilder for PrefabBuilder {
+    fn build_map(&self, width: i16, height: i16, rng: &mut StdRng) -> Map {
+        const MAX_PREFABS: usize = 2;
+        const MAX_TRIES: usize = 20;
+
+        let mut map = self.base.build_map(width, height, rng);
+
+        let mut prefabs: Vec<&Prefab> = self.prefabs.iter().collect();
+        prefabs.shuffle(rng);
+        let mut placed = 0;
+
+        for prefab in prefabs {
+            if placed >= MAX_PREFABS {
+                break;
+            }
+            for _ in 0..MAX_TRIES {
+                let octant = Octant(rng.gen_range(0..8));
+                if try_place(&mut map, &prefab.transformed(octant), rng) {
+                    placed += 1;
+                    break;
+                }
+            }
+        }
+
+        map
+    }
+}
+
+/// Places the prefab at a random spot that is inside the borders, and
+/// doesn't overlap any room or the start. Returns whether there was such a
+/// spot.
+fn try_place(map: &mut Map, prefab: &Prefab, rng: &mut StdRng) -> bool {
+    if prefab.width > map.width - 2 || prefab.height > map.height - 2 {
+        return false;
+    }
+
+    let x = rng.gen_range(1..=map.width - prefab.width - 1);
+    let y = rng.gen_range(1..=map.height - prefab.height - 1);
+    let area = Rect::new(x, y, prefab.width, prefab.height);
+    let (start_x, start_y) = map.start;
+    let contains = |x, y| x >= area.x1 && x < area.x2 && y >= area.y1 && y < area.y2;
+
+    if contains(start_x, start_y) || map.rooms.iter().any(|room| area.intersect(room)) {
+        return false;
+    }
+
+    // The reachable floor tile outside the prefab closest to it.
+    let center = Point::new(x + prefab.width / 2, y + prefab.height / 2);
+    let target = reachable_tiles(map)
+        .iter()
+        .enumerate()
+        .filter(|&(_, &reachable)| reachable)
+        .map(|(idx, _)| map.idx_xy(idx))
+        .filter(|&(x, y)| !contains(x, y))
+        .min_by_key(|&(x, y)| center.distance_squared(Point::new(x, y)));
+    let target = match target {
+        Some(target) => target,
+        None => return false,
+    };
+
+    prefab.apply_to_map(map, x, y);
+
+    // Dig from the floor tile of the prefab closest to the target.
+    let target_point = Point::new(target.0, target.1);
+    let entrance = (0..prefab.height)
+        .flat_map(|dy| (0..prefab.width).map(move |dx| (dx, dy)))
+        .filter(|&(dx, dy)| {
+            matches!(
+                prefab.cell(dx, dy),
+                PrefabCell::Tile(TileType::Floor) | PrefabCell::Spawn(_)
+            )
+        })
+        .map(|(dx, dy)| (x + dx, y + dy))
+        .min_by_key(|&(x, y)| target_point.distance_squared(Point::new(x, y)));
+    if let Some(entrance) = entrance {
+        draw_corridor(map, entrance, target);
+    }
+
+    map.rooms.push(area);
+    true
+}
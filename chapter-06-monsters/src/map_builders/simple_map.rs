@@ -0,0 +1,55 @@
+use crate::{
+    map::Map,
+    map_builders::{common::set_start, MapBuilder},
+    rect::Rect,
+};
+use rand::{rngs::StdRng, Rng};
+
+/// Random rooms, each connected to the previous one by an L-shaped
+/// corridor.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SimpleMapBuilder;
+
+impl MapBuilder for SimpleMapBuilder {
+    fn build_map(&self, width: i16, height: i16, rng: &mut StdRng) -> Map {
+        let mut map = Map::new(width, height);
+
+        const MAX_ROOMS: u8 = 30;
+        const MIN_SIZE: i16 = 6;
+        const MAX_SIZE: i16 = 10;
+
+        for _ in 0..MAX_ROOMS {
+            let w = rng.gen_range(MIN_SIZE..MAX_SIZE);
+            let h = rng.gen_range(MIN_SIZE..MAX_SIZE) / 2;
+            let x = rng.gen_range(1..map.width - w - 1);
+            let y = rng.gen_range(1..map.height - h - 1);
+            let new_room = Rect::new(x, y, w, h);
+            let mut ok = true;
+            for other_room in map.rooms.iter() {
+                if new_room.intersect(other_room) {
+                    ok = false
+                }
+            }
+            if ok {
+                map.apply_room_to_map(&new_room);
+
+                if !map.rooms.is_empty() {
+                    let (new_x, new_y) = new_room.center();
+                    let (prev_x, prev_y) = map.rooms[map.rooms.len() - 1].center();
+                    if rng.gen() {
+                        map.apply_horizontal_tunnel(prev_x, new_x, prev_y);
+                        map.apply_vertical_tunnel(prev_y, new_y, new_x);
+                    } else {
+                        map.apply_vertical_tunnel(prev_y, new_y, prev_x);
+                        map.apply_horizontal_tunnel(prev_x, new_x, new_y);
+                    }
+                }
+
+                map.rooms.push(new_room);
+            }
+        }
+
+        set_start(&mut map);
+        map
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{
+    map::{Map, TileType},
+    map_builders::{common::set_start, MapBuilder},
+};
+use rand::{rngs::StdRng, Rng};
+use roguelike_algorithms::Point;
+use std::collections::HashMap;
+
+/// Regions around random seeds, separated by walls where they meet.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VoronoiBuilder;
+
+impl MapBuilder for VoronoiBuilder {
+    fn build_map(&self, width: i16, height: i16, rng: &mut StdRng) -> Map {
+        let mut map = Map::new(width, height);
+
+        const SEEDS: usize = 32;
+
+        let seeds: Vec<Point> = (0..SEEDS)
+            .map(|_| Point::new(rng.gen_range(1..width - 1), rng.gen_range(1..height - 1)))
+            .collect();
+
+        let region: Vec<usize> = (0..map.tiles.len())
+            .map(|idx| {
+                let (x, y) = map.idx_xy(idx);
+                let point = Point::new(x, y);
+                (0..SEEDS)
+                    .min_by_key(|&i| seeds[i].distance_squared(point))
+                    .unwrap()
+            })
+            .collect();
+
+        // Every tile next to another region is a wall, so that each region
+        // is walled in on its own side of the border.
+        let mut border = vec![true; map.tiles.len()];
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                let idx = map.xy_idx(x, y);
+                border[idx] = Point::new(x, y)
+                    .neighbours_4()
+                    .any(|p| region[map.xy_idx(p.x, p.y)] != region[idx]);
+                if !border[idx] {
+                    map.tiles[idx] = TileType::Floor;
+                }
+            }
+        }
+
+        // Then a doorway is carved through the border of every pair of
+        // neighbouring regions, at a random place where it joins the inside
+        // of both.
+        let mut doorways: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+        for y in 2..height - 2 {
+            for x in 2..width - 2 {
+                let here = Point::new(x, y);
+                for &direction in &[Point::EAST, Point::SOUTH] {
+                    let there = here + direction;
+                    let before = map.xy_idx(here.x - direction.x, here.y - direction.y);
+                    let after = map.xy_idx(there.x + direction.x, there.y + direction.y);
+                    let (a, b) = (map.xy_idx(here.x, here.y), map.xy_idx(there.x, there.y));
+                    if region[a] != region[b]
+                        && region[before] == region[a]
+                        && region[after] == region[b]
+                        && !border[before]
+                        && !border[after]
+                    {
+                        let pair = (region[a].min(region[b]), region[a].max(region[b]));
+                        doorways.entry(pair).or_default().push((a, b));
+                    }
+                }
+            }
+        }
+        let mut pairs: Vec<_> = doorways.into_iter().collect();
+        // Sorted, so that the same seed always carves the same doorways.
+        pairs.sort_unstable_by_key(|&(pair, _)| pair);
+        for (_, candidates) in pairs {
+            let (a, b) = candidates[rng.gen_range(0..candidates.len())];
+            map.tiles[a] = TileType::Floor;
+            map.tiles[b] = TileType::Floor;
+        }
+
+        set_start(&mut map);
+        map
+    }
+}
@@ -0,0 +1,184 @@
+use crate::{
+    dungeon::Dungeon,
+    game_log::GameLog,
+    gui::{box_size, chosen_entry, draw_box, draw_menu, menu_lines},
+    map::Map,
+    run_state::{RunState, Turn},
+    save_load::SaveGame,
+    seed::Seed,
+    spawner::despawn_all,
+};
+use bevy::{app::AppExit, prelude::*};
+use bevy_crossterm::Terminal;
+
+/// How many of the last messages of the log the game-over screen shows.
+const LAST_MOMENTS: usize = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MainMenuEntry {
+    NewGame,
+    Continue,
+    Quit,
+}
+
+/// The main menu, before a game starts.
+#[derive(Debug, Default)]
+pub struct MainMenu {
+    /// Why the saved game could not be continued, if it could not.
+    pub error: Option<String>,
+}
+
+/// The entries of the main menu: continuing is only offered if there is a
+/// saved game.
+fn main_menu_entries() -> Vec<(MainMenuEntry, String)> {
+    let mut entries = vec![(MainMenuEntry::NewGame, "New game".to_string())];
+    if SaveGame::exists() {
+        entries.push((MainMenuEntry::Continue, "Continue".to_string()));
+    }
+    entries.push((MainMenuEntry::Quit, "Quit".to_string()));
+    entries
+}
+
+/// Draws a box with a title and a list of lines, at the middle of the
+/// terminal, over a blank screen.
+fn draw_screen(term: &mut Terminal, title: &str, lines: &[String]) {
+    term.cls();
+    let (width, height) = box_size(title, lines);
+    let (term_width, term_height) = term.size();
+    let x = term_width.saturating_sub(width) / 2;
+    let y = term_height.saturating_sub(height) / 2;
+    draw_box(term, x, y, title, lines);
+}
+
+pub fn main_menu_input_system(
+    keys: Res<Input<KeyCode>>,
+    mut state: ResMut<State<RunState>>,
+    mut app_exit: ResMut<Events<AppExit>>,
+    mut menu: ResMut<MainMenu>,
+) {
+    if keys.pressed(KeyCode::Escape) {
+        app_exit.send(AppExit);
+        return;
+    }
+
+    let entry = match chosen_entry(&keys, &main_menu_entries()) {
+        Some(&(entry, _)) => entry,
+        None => return,
+    };
+    menu.error = None;
+    match entry {
+        MainMenuEntry::NewGame => state.set_next(RunState::NewGame).unwrap(),
+        MainMenuEntry::Continue => state.set_next(RunState::LoadGame).unwrap(),
+        MainMenuEntry::Quit => app_exit.send(AppExit),
+    }
+}
+
+pub fn draw_main_menu_system(
+    mut term: ResMut<Terminal>,
+    state: Res<State<RunState>>,
+    menu: Res<MainMenu>,
+) {
+    if *state.current() != RunState::MainMenu {
+        return;
+    }
+
+    let mut lines = menu_lines(&main_menu_entries(), "");
+    if let Some(error) = &menu.error {
+        lines.push(String::new());
+        lines.push(format!("Cannot continue: {}", error));
+    }
+    draw_screen(&mut term, "Roguelike Tutorial", &lines);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PauseMenuEntry {
+    Resume,
+    Help,
+    SaveAndQuit,
+}
+
+fn pause_menu_entries() -> Vec<(PauseMenuEntry, String)> {
+    vec![
+        (PauseMenuEntry::Resume, "Resume".to_string()),
+        (PauseMenuEntry::Help, "Key bindings".to_string()),
+        (PauseMenuEntry::SaveAndQuit, "Save and quit".to_string()),
+    ]
+}
+
+pub fn pause_menu_input_system(keys: Res<Input<KeyCode>>, mut state: ResMut<State<RunState>>) {
+    if keys.pressed(KeyCode::Escape) {
+        state.set_next(RunState::AwaitingInput).unwrap();
+        return;
+    }
+
+    match chosen_entry(&keys, &pause_menu_entries()) {
+        Some((PauseMenuEntry::Resume, _)) => state.set_next(RunState::AwaitingInput).unwrap(),
+        Some((PauseMenuEntry::Help, _)) => state.set_next(RunState::ShowHelp).unwrap(),
+        Some((PauseMenuEntry::SaveAndQuit, _)) => state.set_next(RunState::SaveGame).unwrap(),
+        None => {}
+    }
+}
+
+pub fn draw_pause_menu_system(
+    mut term: ResMut<Terminal>,
+    map: Res<Map>,
+    state: Res<State<RunState>>,
+) {
+    if *state.current() != RunState::Menu {
+        return;
+    }
+
+    let lines = menu_lines(&pause_menu_entries(), "");
+    draw_menu(&mut term, &map, "Paused (Esc to resume)", &lines);
+}
+
+/// Goes back to the main menu with Enter or Esc, or quits with `q`. The
+/// dead game is despawned first, so that the dead player does not end the
+/// next one.
+pub fn game_over_input_system(
+    commands: &mut Commands,
+    keys: Res<Input<KeyCode>>,
+    mut state: ResMut<State<RunState>>,
+    mut app_exit: ResMut<Events<AppExit>>,
+    entities: Query<Entity>,
+) {
+    if keys.pressed(KeyCode::Q) {
+        app_exit.send(AppExit);
+    } else if keys.pressed(KeyCode::Return) || keys.pressed(KeyCode::Escape) {
+        despawn_all(commands, &entities);
+        state.set_next(RunState::MainMenu).unwrap();
+    }
+}
+
+/// Sums up the game of the dead player: how deep they went, for how long,
+/// and the last messages of the log.
+pub fn draw_game_over_system(
+    mut term: ResMut<Terminal>,
+    state: Res<State<RunState>>,
+    seed: Res<Seed>,
+    dungeon: Res<Dungeon>,
+    turn: Res<Turn>,
+    log: Res<GameLog>,
+) {
+    if *state.current() != RunState::GameOver {
+        return;
+    }
+
+    let mut lines = vec![
+        format!(
+            "You died on depth {}, on turn {}.",
+            dungeon.depth + 1,
+            turn.0
+        ),
+        format!("Seed: {}", *seed),
+        String::new(),
+        "Your last moments:".to_string(),
+    ];
+    let entries = log.entries();
+    for entry in &entries[entries.len().saturating_sub(LAST_MOMENTS)..] {
+        lines.push(entry.line());
+    }
+    lines.push(String::new());
+    lines.push("Enter for the main menu, Q to quit.".to_string());
+    draw_screen(&mut term, "Game over", &lines);
+}
@@ -0,0 +1,198 @@
+use crate::{
+    components::{
+        BlocksTile, CombatStats, Energy, Monster, Player, Position, Viewshed, WantsToMelee,
+    },
+    initiative::{Action, ENERGY_THRESHOLD},
+    map::{Map, TileType},
+    noise::Noises,
+    spatial_index::SpatialIndex,
+};
+use bevy::prelude::*;
+use rand::{rngs::StdRng, seq::IteratorRandom, Rng};
+use roguelike_algorithms::{
+    pathfinding::a_star,
+    sound::{propagate_sound, SoundField},
+    Point,
+};
+
+/// A monster flees once its HP is at most `1 / FLEE_HP_FRACTION` of its
+/// max HP.
+const FLEE_HP_FRACTION: i32 = 4;
+
+/// How far a monster goes towards a noise it heard: it can tell where the
+/// noise comes from, but not how far it is.
+const HEARING_STEPS: usize = 3;
+
+/// What a monster does with its turn.
+enum Decision {
+    Attack,
+    Step(Point),
+    Wait,
+}
+
+/// Where a monster at `here` goes to look for what made a noise: a few
+/// steps back along the way the sound came. `None` if it does not hear it,
+/// or made it.
+fn towards_noise(noise: &SoundField, here: Point) -> Option<Point> {
+    let mut point = here;
+    for _ in 0..HEARING_STEPS {
+        match noise.heard(point) {
+            Some(heard) if heard.from != point => point = heard.from,
+            _ => break,
+        }
+    }
+    if point == here {
+        None
+    } else {
+        Some(point)
+    }
+}
+
+/// Whether a monster would step onto `p`: it is walkable, free, and not
+/// harmful.
+fn can_step(map: &Map, index: &SpatialIndex, p: Point) -> bool {
+    let info = map.tiles[map.xy_idx(p.x, p.y)].info();
+    info.walkable && info.damage == 0 && !index.is_blocked(p.x, p.y)
+}
+
+/// Steps away from the player, to the neighbouring tile farthest from
+/// them.
+fn flee(map: &Map, index: &SpatialIndex, here: Point, player: Point) -> Decision {
+    let distance = here.chebyshev_distance(player);
+    let farthest = here
+        .neighbours_8()
+        .filter(|&p| can_step(map, index, p))
+        .max_by_key(|&p| (p.chebyshev_distance(player), p.manhattan_distance(player)))
+        .filter(|&p| p.chebyshev_distance(player) > distance);
+
+    match farthest {
+        Some(p) => Decision::Step(p),
+        // Cornered.
+        None if distance == 1 => Decision::Attack,
+        None => Decision::Wait,
+    }
+}
+
+/// Steps to a random neighbouring tile, or stays for a while.
+fn wander(map: &Map, index: &SpatialIndex, here: Point, rng: &mut StdRng) -> Decision {
+    if rng.gen_bool(0.5) {
+        return Decision::Wait;
+    }
+    here.neighbours_8()
+        .filter(|&p| can_step(map, index, p))
+        .choose(rng)
+        .map_or(Decision::Wait, Decision::Step)
+}
+
+/// Monsters that can act chase the player when they see them, attack when
+/// next to them, and flee when badly hurt. A monster that does not see the
+/// player goes where it last saw them, or towards the loudest noise it
+/// heard, and wanders around otherwise.
+#[allow(clippy::type_complexity)]
+pub fn monster_ai_system(
+    commands: &mut Commands,
+    mut map: ResMut<Map>,
+    mut rng: ResMut<StdRng>,
+    mut index: ResMut<SpatialIndex>,
+    mut noises: ResMut<Noises>,
+    mut query: Query<(
+        Entity,
+        &mut Position,
+        &mut Viewshed,
+        &mut Energy,
+        Option<&mut Monster>,
+        Option<&CombatStats>,
+        Option<&Player>,
+        Option<&BlocksTile>,
+    )>,
+) {
+    let mut player = None;
+    for (entity, pos, _, _, _, _, is_player, _) in query.iter_mut() {
+        if is_player.is_some() {
+            player = Some((entity, Point::new(pos.x, pos.y)));
+        }
+    }
+    let (player_entity, player) = match player {
+        Some(player) => player,
+        None => return,
+    };
+    let heard: Vec<_> = noises
+        .0
+        .drain(..)
+        .map(|noise| propagate_sound(&*map, noise.position, noise.volume))
+        .collect();
+
+    for (entity, mut pos, mut viewshed, mut energy, monster, stats, _, blocks) in query.iter_mut() {
+        let mut monster = match monster {
+            Some(monster) => monster,
+            None => continue,
+        };
+        // Monsters hear even when they can't act yet.
+        let here = Point::new(pos.x, pos.y);
+        let loudest = heard
+            .iter()
+            .filter_map(|noise| Some((noise.volume(here), towards_noise(noise, here)?)))
+            .max_by_key(|&(volume, _)| volume);
+        if let Some((_, towards)) = loudest {
+            monster.destination = Some((towards.x, towards.y));
+        }
+        if energy.0 < ENERGY_THRESHOLD {
+            continue;
+        }
+
+        let sees_player = viewshed.visible_tiles.contains(player);
+        if sees_player {
+            monster.destination = Some((player.x, player.y));
+        }
+        let hurt = matches!(stats, Some(stats) if stats.hp * FLEE_HP_FRACTION <= stats.max_hp);
+
+        let decision = if sees_player && hurt {
+            flee(&map, &index, here, player)
+        } else if sees_player && here.chebyshev_distance(player) == 1 {
+            Decision::Attack
+        } else {
+            let target = monster
+                .destination
+                .map(|(x, y)| Point::new(x, y))
+                .filter(|&target| target != here);
+            let path = target.and_then(|target| a_star(&*map, here, target, true));
+            match path.as_ref().and_then(|path| path.first()) {
+                Some(&next) => Decision::Step(next),
+                None => {
+                    // Got there, or can't get there: lost track.
+                    monster.destination = None;
+                    wander(&map, &index, here, &mut rng)
+                }
+            }
+        };
+
+        energy.0 -= match decision {
+            Decision::Attack => {
+                commands.insert_one(
+                    entity,
+                    WantsToMelee {
+                        target: player_entity,
+                    },
+                );
+                Action::Attack.cost()
+            }
+            Decision::Step(next) => {
+                let idx = map.xy_idx(next.x, next.y);
+                if map.tiles[idx] == TileType::ClosedDoor {
+                    map.tiles[idx] = TileType::OpenDoor;
+                    viewshed.dirty = true;
+                    Action::Move.cost()
+                } else if index.is_blocked(next.x, next.y) {
+                    Action::Wait.cost()
+                } else {
+                    index.move_entity(entity, (here.x, here.y), (next.x, next.y), blocks.is_some());
+                    pos.x = next.x;
+                    pos.y = next.y;
+                    viewshed.dirty = true;
+                    Action::Move.cost() * map.tiles[idx].info().movement_cost as i32
+                }
+            }
+            Decision::Wait => Action::Wait.cost(),
+        };
+    }
+}
@@ -0,0 +1,31 @@
+use roguelike_algorithms::Point;
+
+/// How loud a step is: only monsters right next to it hear it.
+pub const STEP_VOLUME: u32 = 2;
+/// How loud a door opening is.
+pub const DOOR_VOLUME: u32 = 6;
+/// How loud a fight is.
+pub const COMBAT_VOLUME: u32 = 8;
+
+/// A sound made by an action, e.g. a fight.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Noise {
+    pub position: Point,
+    pub volume: u32,
+}
+
+/// The noises made since the monsters last listened.
+///
+/// Actions add to it with `make`, and the monster AI empties it every time
+/// it runs, whether the monsters that hear a noise can act yet or not.
+#[derive(Debug, Default)]
+pub struct Noises(pub Vec<Noise>);
+
+impl Noises {
+    pub fn make(&mut self, (x, y): (i16, i16), volume: u32) {
+        self.0.push(Noise {
+            position: Point::new(x, y),
+            volume,
+        });
+    }
+}
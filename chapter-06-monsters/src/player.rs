@@ -1,44 +1,205 @@
 use crate::{
-    components::{Player, Position, Viewshed},
+    combat::Damage,
+    components::{
+        CombatStats, Energy, Item, Player, Position, Viewshed, WantsToMelee, WantsToPickupItem,
+    },
+    dungeon::{ChangeLevel, Dungeon},
+    game_log::GameLog,
+    initiative::{spend_player_energy, Action},
+    key_bindings::{InputAction, KeyBindings},
     map::{Map, TileType},
+    noise::{Noises, DOOR_VOLUME, STEP_VOLUME},
+    run_state::{RunState, Turn},
+    spatial_index::SpatialIndex,
 };
 use bevy::prelude::*;
+use bevy_crossterm::crossterm::style::Color;
 use std::cmp::{max, min};
 
+/// Moves the player, attacks the monster in the way, or opens the door in
+/// the way. Stepping onto a harmful tile hurts, and steps and doors make
+/// noise. Returns the energy it cost, if the player did anything.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn try_move_player(
     delta_x: i16,
     delta_y: i16,
-    map: &Res<Map>,
-    query: &mut Query<(&mut Position, &mut Viewshed), With<Player>>,
-) {
-    for (mut pos, mut viewshed) in query.iter_mut() {
+    commands: &mut Commands,
+    map: &mut ResMut<Map>,
+    index: &mut SpatialIndex,
+    query: &mut Query<(Entity, &mut Position, &mut Viewshed), With<Player>>,
+    targets: &Query<&CombatStats, Without<Player>>,
+    damage: &mut Events<Damage>,
+    log: &mut GameLog,
+    turn: &Turn,
+    noises: &mut Noises,
+) -> Option<i32> {
+    let mut cost = None;
+    for (entity, mut pos, mut viewshed) in query.iter_mut() {
         let new_x = min(map.width - 1, max(0, pos.x + delta_x));
         let new_y = min(map.height - 1, max(0, pos.y + delta_y));
         let destination_idx = map.xy_idx(pos.x + delta_x, pos.y + delta_y);
-        if map.tiles[destination_idx] == TileType::Floor {
+        let target = index
+            .entities_at(new_x, new_y)
+            .iter()
+            .copied()
+            .find(|&target| targets.get(target).is_ok());
+        if let Some(target) = target {
+            commands.insert_one(entity, WantsToMelee { target });
+            cost = Some(Action::Attack.cost());
+        } else if map.tiles[destination_idx] == TileType::ClosedDoor {
+            // Bumping into a closed door opens it, and takes the move.
+            map.tiles[destination_idx] = TileType::OpenDoor;
+            viewshed.dirty = true;
+            noises.make((new_x, new_y), DOOR_VOLUME);
+            cost = Some(Action::Move.cost());
+        } else if map.tiles[destination_idx].is_walkable() && !index.is_blocked(new_x, new_y) {
+            index.move_entity(entity, (pos.x, pos.y), (new_x, new_y), true);
             pos.x = new_x;
             pos.y = new_y;
 
             viewshed.dirty = true;
+            noises.make((new_x, new_y), STEP_VOLUME);
+            let info = map.tiles[destination_idx].info();
+            if info.damage > 0 {
+                damage.send(Damage {
+                    target: entity,
+                    amount: info.damage,
+                });
+                log.add(turn.0, format!("The {} burns you.", info.name), Color::Red);
+            }
+            cost = Some(Action::Move.cost() * info.movement_cost as i32);
         }
     }
+    cost
+}
+
+/// Takes the stairs the player stands on, if they lead `down` or up as
+/// wanted. Returns the energy it cost, if the player did take them.
+fn try_take_stairs(
+    down: bool,
+    map: &Map,
+    dungeon: &Dungeon,
+    change_level: &mut Events<ChangeLevel>,
+    query: &mut Query<(Entity, &mut Position, &mut Viewshed), With<Player>>,
+) -> Option<i32> {
+    let mut cost = None;
+    for (_, pos, _) in query.iter_mut() {
+        let depth = match map.tiles[map.xy_idx(pos.x, pos.y)] {
+            TileType::DownStairs if down => dungeon.depth + 1,
+            TileType::UpStairs if !down && dungeon.depth > 0 => dungeon.depth - 1,
+            _ => continue,
+        };
+        change_level.send(ChangeLevel { depth });
+        cost = Some(Action::Move.cost());
+    }
+    cost
 }
 
+/// Picks up an item the player stands on. Returns the energy it cost, if
+/// there was one.
+fn try_pick_up(
+    commands: &mut Commands,
+    index: &SpatialIndex,
+    query: &mut Query<(Entity, &mut Position, &mut Viewshed), With<Player>>,
+    items: &Query<&Item>,
+) -> Option<i32> {
+    let mut cost = None;
+    for (entity, pos, _) in query.iter_mut() {
+        let item = index
+            .entities_at(pos.x, pos.y)
+            .iter()
+            .copied()
+            .find(|&item| items.get(item).is_ok());
+        if let Some(item) = item {
+            commands.insert_one(entity, WantsToPickupItem { item });
+            cost = Some(Action::HandleItem.cost());
+        }
+    }
+    cost
+}
+
+/// Waits for the player to act, and ends the wait once they did.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn player_input_system(
+    commands: &mut Commands,
     keys: Res<Input<KeyCode>>,
-    map: Res<Map>,
-    mut query: Query<(&mut Position, &mut Viewshed), With<Player>>,
+    mut state: ResMut<State<RunState>>,
+    mut map: ResMut<Map>,
+    dungeon: Res<Dungeon>,
+    mut change_level: ResMut<Events<ChangeLevel>>,
+    mut index: ResMut<SpatialIndex>,
+    key_bindings: Res<KeyBindings>,
+    mut damage: ResMut<Events<Damage>>,
+    mut log: ResMut<GameLog>,
+    turn: Res<Turn>,
+    mut noises: ResMut<Noises>,
+    mut query: Query<(Entity, &mut Position, &mut Viewshed), With<Player>>,
+    targets: Query<&CombatStats, Without<Player>>,
+    items: Query<&Item>,
+    mut energy: Query<&mut Energy, With<Player>>,
 ) {
-    if keys.pressed(KeyCode::Left) || keys.pressed(KeyCode::H) {
-        try_move_player(-1, 0, &map, &mut query);
-    }
-    if keys.pressed(KeyCode::Right) || keys.pressed(KeyCode::L) {
-        try_move_player(1, 0, &map, &mut query);
-    }
-    if keys.pressed(KeyCode::Up) || keys.pressed(KeyCode::K) {
-        try_move_player(0, -1, &map, &mut query);
+    if keys.pressed(KeyCode::Escape) {
+        state.set_next(RunState::Menu).unwrap();
+        return;
     }
-    if keys.pressed(KeyCode::Down) || keys.pressed(KeyCode::J) {
-        try_move_player(0, 1, &map, &mut query);
+
+    let action = match key_bindings.pressed(&keys) {
+        Some(action) => action,
+        None => return,
+    };
+    let cost = match action {
+        InputAction::Inventory => {
+            state.set_next(RunState::ShowInventory).unwrap();
+            return;
+        }
+        InputAction::Drop => {
+            state.set_next(RunState::ShowDropItem).unwrap();
+            return;
+        }
+        InputAction::Equipment => {
+            state.set_next(RunState::ShowEquipment).unwrap();
+            return;
+        }
+        InputAction::Log => {
+            state.set_next(RunState::ShowLog).unwrap();
+            return;
+        }
+        InputAction::Help => {
+            state.set_next(RunState::ShowHelp).unwrap();
+            return;
+        }
+        InputAction::Look => {
+            state.set_next(RunState::Look).unwrap();
+            return;
+        }
+        InputAction::Descend => {
+            try_take_stairs(true, &map, &dungeon, &mut change_level, &mut query)
+        }
+        InputAction::Ascend => {
+            try_take_stairs(false, &map, &dungeon, &mut change_level, &mut query)
+        }
+        InputAction::PickUp => try_pick_up(commands, &index, &mut query, &items),
+        InputAction::Wait => Some(Action::Wait.cost()),
+        _ => match action.delta() {
+            Some((delta_x, delta_y)) => try_move_player(
+                delta_x,
+                delta_y,
+                commands,
+                &mut map,
+                &mut index,
+                &mut query,
+                &targets,
+                &mut damage,
+                &mut log,
+                &turn,
+                &mut noises,
+            ),
+            None => None,
+        },
+    };
+
+    if let Some(cost) = cost {
+        spend_player_energy(&mut energy, cost);
+        state.set_next(RunState::PlayerTurn).unwrap();
     }
 }
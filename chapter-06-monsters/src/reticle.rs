@@ -0,0 +1,179 @@
+use crate::{
+    components::{Energy, Monster, Player, Position, Viewshed, WantsToUseItem},
+    initiative::{spend_player_energy, Action},
+    key_bindings::{InputAction, KeyBindings},
+    run_state::RunState,
+};
+use bevy::prelude::*;
+use bevy_crossterm::{crossterm::style::Color, Terminal};
+use roguelike_algorithms::Point;
+
+/// A tile the player points at with the movement keys, to look at it in
+/// look mode, or to use a ranged item on it.
+#[derive(Debug, Default)]
+pub struct Reticle {
+    pub position: (i16, i16),
+    /// The ranged item to use on the reticle, and its range, when
+    /// targeting.
+    pub targeting: Option<(Entity, i16)>,
+}
+
+impl Reticle {
+    /// A reticle on the player, to look around.
+    pub fn look(pos: &Position) -> Reticle {
+        Reticle {
+            position: (pos.x, pos.y),
+            targeting: None,
+        }
+    }
+
+    /// A reticle on the player, to choose where to use a ranged item.
+    pub fn target(pos: &Position, item: Entity, range: i16) -> Reticle {
+        Reticle {
+            position: (pos.x, pos.y),
+            targeting: Some((item, range)),
+        }
+    }
+
+    /// Whether the reticle can go to a tile: it must be in view of the
+    /// player, and within the range of the item when targeting.
+    pub fn can_reach(&self, pos: &Position, viewshed: &Viewshed, point: Point) -> bool {
+        let in_range = match self.targeting {
+            Some((_, range)) => {
+                let range = i64::from(range);
+                point.distance_squared(Point::new(pos.x, pos.y)) <= (range * range).into()
+            }
+            None => true,
+        };
+        in_range && viewshed.visible_tiles.contains(point)
+    }
+
+    /// Moves the reticle with the movement keys, or to the next monster it
+    /// can reach with Tab, nearest first.
+    fn update(
+        &mut self,
+        keys: &Input<KeyCode>,
+        key_bindings: &KeyBindings,
+        pos: &Position,
+        viewshed: &Viewshed,
+        monsters: &Query<&Position, With<Monster>>,
+    ) {
+        let current = Point::new(self.position.0, self.position.1);
+        if let Some((delta_x, delta_y)) = key_bindings.movement_delta(keys) {
+            let next = Point::new(current.x + delta_x, current.y + delta_y);
+            if self.can_reach(pos, viewshed, next) {
+                self.position = (next.x, next.y);
+            }
+        } else if keys.pressed(KeyCode::Tab) {
+            let player = Point::new(pos.x, pos.y);
+            let mut targets: Vec<_> = monsters
+                .iter()
+                .map(|monster| Point::new(monster.x, monster.y))
+                .filter(|&point| self.can_reach(pos, viewshed, point))
+                .collect();
+            targets.sort_by_key(|&point| (point.distance_squared(player), point.y, point.x));
+            let next = targets
+                .iter()
+                .position(|&point| point == current)
+                .map_or(0, |i| i + 1);
+            if let Some(&target) = targets.get(next).or_else(|| targets.first()) {
+                self.position = (target.x, target.y);
+            }
+        }
+    }
+}
+
+/// Puts the reticle on the player when look mode starts.
+pub fn start_look_system(mut reticle: ResMut<Reticle>, players: Query<&Position, With<Player>>) {
+    for pos in players.iter() {
+        *reticle = Reticle::look(pos);
+    }
+}
+
+/// Moves the reticle around in look mode, until Esc or the look key.
+pub fn look_input_system(
+    keys: Res<Input<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut state: ResMut<State<RunState>>,
+    mut reticle: ResMut<Reticle>,
+    players: Query<(&Position, &Viewshed), With<Player>>,
+    monsters: Query<&Position, With<Monster>>,
+) {
+    if keys.pressed(KeyCode::Escape) || key_bindings.pressed(&keys) == Some(InputAction::Look) {
+        state.set_next(RunState::AwaitingInput).unwrap();
+        return;
+    }
+
+    for (pos, viewshed) in players.iter() {
+        reticle.update(&keys, &key_bindings, pos, viewshed, &monsters);
+    }
+}
+
+/// Moves the reticle, and uses the ranged item on it with Enter.
+#[allow(clippy::too_many_arguments)]
+pub fn targeting_input_system(
+    commands: &mut Commands,
+    keys: Res<Input<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut state: ResMut<State<RunState>>,
+    mut reticle: ResMut<Reticle>,
+    players: Query<(Entity, &Position, &Viewshed), With<Player>>,
+    monsters: Query<&Position, With<Monster>>,
+    mut energy: Query<&mut Energy, With<Player>>,
+) {
+    let item = match reticle.targeting {
+        Some((item, _)) if !keys.pressed(KeyCode::Escape) => item,
+        _ => {
+            reticle.targeting = None;
+            state.set_next(RunState::AwaitingInput).unwrap();
+            return;
+        }
+    };
+
+    for (player, pos, viewshed) in players.iter() {
+        if keys.pressed(KeyCode::Return) {
+            commands.insert_one(
+                player,
+                WantsToUseItem {
+                    item,
+                    target: Some(reticle.position),
+                },
+            );
+            reticle.targeting = None;
+            spend_player_energy(&mut energy, Action::UseItem.cost());
+            state.set_next(RunState::PlayerTurn).unwrap();
+        } else {
+            reticle.update(&keys, &key_bindings, pos, viewshed, &monsters);
+        }
+    }
+}
+
+/// Draws the reticle, and highlights the tiles it can reach when
+/// targeting.
+pub fn draw_reticle_system(
+    mut term: ResMut<Terminal>,
+    state: Res<State<RunState>>,
+    reticle: Res<Reticle>,
+    players: Query<(&Position, &Viewshed), With<Player>>,
+) {
+    match state.current() {
+        RunState::Look => {}
+        RunState::ShowTargeting => {
+            for (pos, viewshed) in players.iter() {
+                for point in viewshed.visible_tiles.iter() {
+                    if reticle.can_reach(pos, viewshed, point) {
+                        term.set_background_color(
+                            point.x as u16,
+                            point.y as u16,
+                            Some(Color::DarkBlue),
+                        );
+                    }
+                }
+            }
+        }
+        _ => return,
+    }
+
+    let (x, y) = reticle.position;
+    term.set_background_color(x as u16, y as u16, Some(Color::Cyan));
+}
@@ -0,0 +1,83 @@
+use crate::{
+    components::{Energy, Player},
+    initiative::player_is_ready,
+};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The stage running the systems of the current `RunState`.
+pub const TURN_STAGE: &str = "turn";
+/// The stage drawing the screen, after the combat stage, in every state.
+pub const RENDER_STAGE: &str = "render";
+
+/// Where the game is in a turn. Systems that change the world only run in
+/// `PlayerTurn` and `MonsterTurn`, so nothing happens while the game waits
+/// for input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RunState {
+    /// The main menu, before a game starts.
+    MainMenu,
+    /// A new game is set up.
+    NewGame,
+    /// The saved game is loaded.
+    LoadGame,
+    /// Waiting for the player to act.
+    AwaitingInput,
+    /// The player acted, and the world reacts to it.
+    PlayerTurn,
+    /// Time passes, and the monsters act, until the player can act again.
+    MonsterTurn,
+    /// The player is dead.
+    GameOver,
+    /// The game is paused, in the pause menu.
+    Menu,
+    /// The game is saved, then the app exits.
+    SaveGame,
+    /// The player chooses an item to use.
+    ShowInventory,
+    /// The player chooses an item to drop.
+    ShowDropItem,
+    /// The player chooses an equipped item to remove.
+    ShowEquipment,
+    /// The player chooses where to use a ranged item.
+    ShowTargeting,
+    /// The player looks around with the reticle.
+    Look,
+    /// The player reads the whole log.
+    ShowLog,
+    /// The player reads the key bindings.
+    ShowHelp,
+}
+
+impl RunState {
+    /// Tells the player what the game waits for, if it is not obvious.
+    pub fn prompt(self) -> Option<&'static str> {
+        match self {
+            RunState::ShowTargeting => {
+                Some("Choose a target, Tab for the next monster, Enter to confirm, Esc to cancel.")
+            }
+            RunState::Look => Some("Looking around. Tab for the next monster, Esc to stop."),
+            _ => None,
+        }
+    }
+}
+
+/// The number of turns the player has taken.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct Turn(pub u32);
+
+pub fn end_player_turn_system(mut state: ResMut<State<RunState>>, mut turn: ResMut<Turn>) {
+    turn.0 += 1;
+    state.set_next(RunState::MonsterTurn).unwrap();
+}
+
+/// Waits for the player again once they have the energy to act. Until
+/// then, the monster turn goes on.
+pub fn end_monster_turn_system(
+    mut state: ResMut<State<RunState>>,
+    players: Query<&Energy, With<Player>>,
+) {
+    if player_is_ready(&players) {
+        state.set_next(RunState::AwaitingInput).unwrap();
+    }
+}
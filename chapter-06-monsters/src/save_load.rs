@@ -0,0 +1,224 @@
+use crate::{
+    components::{EquipmentSlot, Equipped, InBackpack, Player, Position, Viewshed},
+    dungeon::{Dungeon, StoredComponents, StoredEntity},
+    game_log::GameLog,
+    map::Map,
+    menus::MainMenu,
+    run_state::{RunState, Turn},
+    seed::{PlayedSeed, Seed},
+    spawner::despawn_all,
+};
+use bevy::{app::AppExit, prelude::*};
+use bevy_crossterm::crossterm::style::Color;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path};
+
+/// Where the game is saved, in the working directory.
+const SAVE_PATH: &str = "savegame.json";
+
+/// The version of the save format. Bump it whenever a saved type changes;
+/// saves of other versions are refused.
+const SAVE_VERSION: u32 = 2;
+
+/// Where a saved entity belongs. The player comes first, so that it is
+/// restored before the items it carries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+enum Place {
+    Player,
+    Level,
+    Backpack,
+    Equipped(EquipmentSlot),
+}
+
+/// Only the version of a save, to check it before reading the rest.
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+/// Everything needed to go on with a game.
+#[derive(Serialize, Deserialize)]
+pub struct SaveGame {
+    version: u32,
+    seed: Seed,
+    /// The state of the game RNG: a seed drawn from it when saving, which
+    /// it is then reseeded with.
+    rng_state: u64,
+    turn: Turn,
+    map: Map,
+    dungeon: Dungeon,
+    log: GameLog,
+    entities: Vec<(Place, StoredEntity)>,
+}
+
+impl SaveGame {
+    /// Reads the saved game, if there is one.
+    pub fn load() -> io::Result<Option<SaveGame>> {
+        let json = match fs::read_to_string(SAVE_PATH) {
+            Ok(json) => json,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let header: Header = serde_json::from_str(&json)?;
+        if header.version != SAVE_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "save version {} is not supported, expected {}",
+                    header.version, SAVE_VERSION
+                ),
+            ));
+        }
+        Ok(Some(serde_json::from_str(&json)?))
+    }
+
+    pub fn exists() -> bool {
+        Path::new(SAVE_PATH).exists()
+    }
+
+    fn write(&self) -> io::Result<()> {
+        fs::write(SAVE_PATH, serde_json::to_string(self)?)
+    }
+}
+
+/// Deletes the saved game, if there is one: a dead character stays dead.
+pub fn delete_save() -> io::Result<()> {
+    match fs::remove_file(SAVE_PATH) {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+/// Replaces whatever was played with the saved game, and deletes it, or
+/// goes back to the main menu if it cannot be loaded.
+#[allow(clippy::too_many_arguments)]
+pub fn load_game_system(
+    commands: &mut Commands,
+    mut state: ResMut<State<RunState>>,
+    mut menu: ResMut<MainMenu>,
+    played_seed: Res<PlayedSeed>,
+    mut seed: ResMut<Seed>,
+    mut rng: ResMut<StdRng>,
+    mut turn: ResMut<Turn>,
+    mut map: ResMut<Map>,
+    mut dungeon: ResMut<Dungeon>,
+    mut log: ResMut<GameLog>,
+    entities: Query<Entity>,
+) {
+    let save = match SaveGame::load() {
+        Ok(Some(save)) => save,
+        Ok(None) => {
+            menu.error = Some("there is no saved game".to_string());
+            state.set_next(RunState::MainMenu).unwrap();
+            return;
+        }
+        Err(err) => {
+            menu.error = Some(err.to_string());
+            state.set_next(RunState::MainMenu).unwrap();
+            return;
+        }
+    };
+
+    despawn_all(commands, &entities);
+    let mut owner = None;
+    for (place, stored) in save.entities {
+        let entity = stored.restore(commands, &save.map);
+        match place {
+            Place::Player => {
+                commands.with(Player {});
+                owner = Some(entity);
+            }
+            Place::Level => {}
+            Place::Backpack => {
+                let owner = owner.expect("the player should be saved before its items");
+                commands.with(InBackpack { owner });
+            }
+            Place::Equipped(slot) => {
+                let owner = owner.expect("the player should be saved before its items");
+                commands.with(Equipped { owner, slot });
+            }
+        }
+    }
+
+    *seed = save.seed;
+    *rng = StdRng::seed_from_u64(save.rng_state);
+    *turn = save.turn;
+    *map = save.map;
+    *dungeon = save.dungeon;
+    *log = save.log;
+    played_seed.set(save.seed);
+
+    // The save is used up, so that quitting without saving does not bring
+    // the game back to it: the next save and quit writes it again.
+    if let Err(err) = delete_save() {
+        log.add(
+            turn.0,
+            format!("Could not delete the saved game: {}", err),
+            Color::Red,
+        );
+    }
+    state.set_next(RunState::AwaitingInput).unwrap();
+}
+
+/// Saves the game and quits. If the save fails, the game goes on.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn save_game_system(
+    mut state: ResMut<State<RunState>>,
+    mut app_exit: ResMut<Events<AppExit>>,
+    seed: Res<Seed>,
+    mut rng: ResMut<StdRng>,
+    turn: Res<Turn>,
+    map: Res<Map>,
+    dungeon: Res<Dungeon>,
+    mut log: ResMut<GameLog>,
+    query: Query<(
+        Option<&Position>,
+        Option<&Player>,
+        Option<&Viewshed>,
+        Option<&InBackpack>,
+        Option<&Equipped>,
+        StoredComponents,
+    )>,
+) {
+    let mut entities = Vec::new();
+    for (position, player, viewshed, in_backpack, equipped, components) in query.iter() {
+        let place = match (player, in_backpack, equipped) {
+            (Some(_), _, _) => Place::Player,
+            (_, Some(_), _) => Place::Backpack,
+            (_, _, Some(equipped)) => Place::Equipped(equipped.slot),
+            _ if position.is_some() => Place::Level,
+            _ => continue,
+        };
+        let stored = StoredEntity::new(position.cloned(), viewshed.cloned(), components);
+        entities.push((place, stored));
+    }
+    entities.sort_by_key(|&(place, _)| place);
+
+    // Reseed the RNG, so that the game goes on the same way whether it is
+    // loaded or not.
+    let rng_state = rng.gen();
+    *rng = StdRng::seed_from_u64(rng_state);
+
+    let save = SaveGame {
+        version: SAVE_VERSION,
+        seed: *seed,
+        rng_state,
+        turn: *turn,
+        map: map.clone(),
+        dungeon: dungeon.clone(),
+        log: log.clone(),
+        entities,
+    };
+    match save.write() {
+        Ok(()) => app_exit.send(AppExit),
+        Err(err) => {
+            log.add(
+                turn.0,
+                format!("Could not save the game: {}", err),
+                Color::Red,
+            );
+            state.set_next(RunState::AwaitingInput).unwrap();
+        }
+    }
+}
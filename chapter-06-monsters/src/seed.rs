@@ -0,0 +1,54 @@
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+/// The seed all randomness in a game derives from.
+///
+/// Each purpose gets its own random number generator, so adding a random
+/// call for one purpose doesn't change what happens for the others.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Seed(pub u64);
+
+impl Seed {
+    /// A random seed.
+    pub fn random() -> Seed {
+        Seed(rand::random())
+    }
+
+    /// A random number generator for `purpose`, e.g. `"map"` or
+    /// `"monsters"`.
+    pub fn rng(self, purpose: &str) -> StdRng {
+        // FNV-1a, since the hashers in `std` are not guaranteed to be
+        // stable across Rust versions.
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for &byte in self.0.to_le_bytes().iter().chain(purpose.as_bytes()) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        StdRng::seed_from_u64(hash)
+    }
+}
+
+/// The seed of the game being played, if any, shared with `main` to print
+/// it once the app has exited.
+#[derive(Clone, Debug, Default)]
+pub struct PlayedSeed(Arc<Mutex<Option<Seed>>>);
+
+impl PlayedSeed {
+    pub fn get(&self) -> Option<Seed> {
+        *self.0.lock().unwrap()
+    }
+
+    pub fn set(&self, seed: Seed) {
+        *self.0.lock().unwrap() = Some(seed);
+    }
+}
+
+impl fmt::Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{
+    components::{BlocksTile, Position},
+    map::Map,
+};
+use bevy::prelude::*;
+
+/// The entities on every tile of the current map, indexed like
+/// `Map::tiles`.
+///
+/// It is rebuilt at the start of every frame, and systems that move
+/// entities during a turn keep it up to date with `move_entity`.
+#[derive(Debug, Default)]
+pub struct SpatialIndex {
+    width: i16,
+    height: i16,
+    /// Whether an entity with `BlocksTile` stands on the tile. Whether the
+    /// tile itself can be walked on is up to `Map`.
+    pub blocked: Vec<bool>,
+    pub tile_content: Vec<Vec<Entity>>,
+}
+
+impl SpatialIndex {
+    fn idx(&self, x: i16, y: i16) -> Option<usize> {
+        if x >= 0 && x < self.width && y >= 0 && y < self.height {
+            Some(y as usize * self.width as usize + x as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Empties the index, resizing it to the map.
+    fn clear(&mut self, width: i16, height: i16) {
+        let size = width as usize * height as usize;
+        self.width = width;
+        self.height = height;
+        self.blocked.clear();
+        self.blocked.resize(size, false);
+        self.tile_content.iter_mut().for_each(Vec::clear);
+        self.tile_content.resize_with(size, Vec::new);
+    }
+
+    /// Whether a blocking entity stands on the tile.
+    pub fn is_blocked(&self, x: i16, y: i16) -> bool {
+        matches!(self.idx(x, y), Some(idx) if self.blocked[idx])
+    }
+
+    /// The entities on the tile, none if it is out of bound.
+    pub fn entities_at(&self, x: i16, y: i16) -> &[Entity] {
+        match self.idx(x, y) {
+            Some(idx) => &self.tile_content[idx],
+            None => &[],
+        }
+    }
+
+    fn add(&mut self, entity: Entity, (x, y): (i16, i16), blocks: bool) {
+        if let Some(idx) = self.idx(x, y) {
+            self.tile_content[idx].push(entity);
+            self.blocked[idx] |= blocks;
+        }
+    }
+
+    /// Moves an entity from one tile to another.
+    ///
+    /// Only one blocking entity stands on a tile, so moving a blocking one
+    /// away unblocks it.
+    pub fn move_entity(&mut self, entity: Entity, from: (i16, i16), to: (i16, i16), blocks: bool) {
+        if let Some(idx) = self.idx(from.0, from.1) {
+            self.tile_content[idx].retain(|&e| e != entity);
+            if blocks {
+                self.blocked[idx] = false;
+            }
+        }
+        self.add(entity, to, blocks);
+    }
+}
+
+pub fn spatial_index_system(
+    map: Res<Map>,
+    mut index: ResMut<SpatialIndex>,
+    query: Query<(Entity, &Position, Option<&BlocksTile>)>,
+) {
+    index.clear(map.width, map.height);
+    for (entity, pos, blocks) in query.iter() {
+        index.add(entity, (pos.x, pos.y), blocks.is_some());
+    }
+}
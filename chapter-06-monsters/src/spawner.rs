@@ -0,0 +1,212 @@
+use crate::{
+    components::{
+        BlocksTile, CombatStats, Consumable, DefenseBonus, Energy, EquipmentSlot, Equippable,
+        InflictsDamage, Item, MeleePowerBonus, Monster, Name, Player, Position, ProvidesHealing,
+        Renderable, Speed, Viewshed,
+    },
+    map::Map,
+};
+use bevy::prelude::*;
+use bevy_crossterm::crossterm::style::Color;
+use rand::{rngs::StdRng, Rng};
+use roguelike_algorithms::{
+    fov::{Map2D, VisibilityGrid},
+    Point,
+};
+
+/// Removes every entity, before a game starts.
+pub fn despawn_all(commands: &mut Commands, entities: &Query<Entity>) {
+    for entity in entities.iter() {
+        commands.despawn(entity);
+    }
+}
+
+pub fn player(commands: &mut Commands, map: &Map, (x, y): (i16, i16)) {
+    commands.spawn((
+        Position { x, y },
+        Renderable {
+            glyph: '@',
+            fg: Some(Color::Yellow),
+            bg: None,
+            render_order: 0,
+        },
+        Player {},
+        BlocksTile,
+        Viewshed {
+            visible_tiles: VisibilityGrid::new(map.width as usize, map.height as usize),
+            range: 8,
+            dirty: true,
+        },
+        Speed(100),
+        Energy::default(),
+        CombatStats {
+            max_hp: 30,
+            hp: 30,
+            power: 5,
+            defense: 2,
+        },
+    ));
+    commands.with(Name {
+        name: "Player".to_string(),
+    });
+}
+
+/// The name, the speed and the combat stats of a monster: bats are fast
+/// and frail, zombies slow and tough.
+fn monster_stats(glyph: char) -> (&'static str, i32, CombatStats) {
+    let (name, speed, hp, power, defense) = match glyph {
+        'b' => ("bat", 200, 6, 2, 0),
+        'z' => ("zombie", 50, 20, 5, 1),
+        'o' => ("orc", 90, 16, 4, 1),
+        _ => ("goblin", 100, 10, 3, 1),
+    };
+    let stats = CombatStats {
+        max_hp: hp,
+        hp,
+        power,
+        defense,
+    };
+    (name, speed, stats)
+}
+
+pub fn monster(commands: &mut Commands, map: &Map, (x, y): (i16, i16), glyph: char) {
+    let (name, speed, stats) = monster_stats(glyph);
+    commands.spawn((
+        Position { x, y },
+        Renderable {
+            glyph,
+            fg: Some(Color::Red),
+            bg: None,
+            render_order: 1,
+        },
+        Monster::default(),
+        BlocksTile,
+        Viewshed {
+            visible_tiles: VisibilityGrid::new(map.width as usize, map.height as usize),
+            range: 8,
+            dirty: true,
+        },
+        Speed(speed),
+        Energy::default(),
+        stats,
+    ));
+    commands.with(Name {
+        name: name.to_string(),
+    });
+}
+
+/// Spawns a random monster in every room but the first, and the monsters
+/// of the prefabs.
+pub fn level_monsters(commands: &mut Commands, map: &Map, rng: &mut StdRng) {
+    for room in map.rooms.iter().skip(1) {
+        let (x, y) = room.center();
+        // The center of a prefab may be a wall, or have a monster of its
+        // own.
+        if !map.tiles[map.xy_idx(x, y)].is_walkable()
+            || map.spawns.iter().any(|&(position, _)| position == (x, y))
+        {
+            continue;
+        }
+
+        let roll = rng.gen_range(0..6);
+        let glyph = match roll {
+            0 | 1 => 'g',
+            2 | 3 => 'o',
+            4 => 'b',
+            _ => 'z',
+        };
+
+        monster(commands, map, (x, y), glyph);
+    }
+
+    for &(position, glyph) in map.spawns.iter() {
+        monster(commands, map, position, glyph);
+    }
+}
+
+fn item(commands: &mut Commands, (x, y): (i16, i16), glyph: char, fg: Color, name: &str) {
+    commands.spawn((
+        Position { x, y },
+        Renderable {
+            glyph,
+            fg: Some(fg),
+            bg: None,
+            render_order: 2,
+        },
+        Name {
+            name: name.to_string(),
+        },
+        Item,
+    ));
+}
+
+pub fn health_potion(commands: &mut Commands, position: (i16, i16)) {
+    item(commands, position, '!', Color::Magenta, "Health Potion");
+    commands
+        .with(Consumable)
+        .with(ProvidesHealing { heal_amount: 8 });
+}
+
+pub fn magic_missile_scroll(commands: &mut Commands, position: (i16, i16)) {
+    item(commands, position, '?', Color::Cyan, "Magic Missile Scroll");
+    commands.with(Consumable).with(InflictsDamage {
+        damage: 8,
+        range: 6,
+        radius: 0,
+    });
+}
+
+pub fn fireball_scroll(commands: &mut Commands, position: (i16, i16)) {
+    item(
+        commands,
+        position,
+        '?',
+        Color::DarkYellow,
+        "Fireball Scroll",
+    );
+    commands.with(Consumable).with(InflictsDamage {
+        damage: 20,
+        range: 6,
+        radius: 3,
+    });
+}
+
+pub fn dagger(commands: &mut Commands, position: (i16, i16)) {
+    item(commands, position, '/', Color::Cyan, "Dagger");
+    commands
+        .with(Equippable {
+            slot: EquipmentSlot::Melee,
+        })
+        .with(MeleePowerBonus { power: 2 });
+}
+
+pub fn shield(commands: &mut Commands, position: (i16, i16)) {
+    item(commands, position, '(', Color::Cyan, "Shield");
+    commands
+        .with(Equippable {
+            slot: EquipmentSlot::Shield,
+        })
+        .with(DefenseBonus { defense: 1 });
+}
+
+/// Spawns a random item at a random place in some of the rooms.
+pub fn level_items(commands: &mut Commands, map: &Map, rng: &mut StdRng) {
+    for room in map.rooms.iter() {
+        if rng.gen_bool(0.5) {
+            continue;
+        }
+        let x = rng.gen_range(room.x1..=room.x2);
+        let y = rng.gen_range(room.y1..=room.y2);
+        if !map.is_in_bound(Point::new(x, y)) || !map.tiles[map.xy_idx(x, y)].is_walkable() {
+            continue;
+        }
+
+        match rng.gen_range(0..6) {
+            0 | 1 => health_potion(commands, (x, y)),
+            2 => magic_missile_scroll(commands, (x, y)),
+            3 => fireball_scroll(commands, (x, y)),
+            4 => dagger(commands, (x, y)),
+            _ => shield(commands, (x, y)),
+        }
+    }
+}
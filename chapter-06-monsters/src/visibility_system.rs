@@ -1,28 +1,39 @@
 use crate::{
     components::{Player, Position, Viewshed},
+    lighting::Lighting,
     map::Map,
 };
 use bevy::prelude::*;
-use roguelike_algorithms::fov::shadow_casting_fov;
+use roguelike_algorithms::{fov::shadow_casting_fov_into, Point};
 
 pub fn visibility_system(
     mut map: ResMut<Map>,
+    mut lighting: ResMut<Lighting>,
     mut query: Query<(Option<&Player>, &mut Viewshed, &Position)>,
+    players: Query<&Position, With<Player>>,
 ) {
+    // The torch moves with the player, whose viewshed is dirty then.
+    if query.iter_mut().any(|(_, viewshed, _)| viewshed.dirty) {
+        let player = players.iter().next().map(|pos| Point::new(pos.x, pos.y));
+        lighting.update(&map, player);
+    }
+
     for (player, mut viewshed, pos) in query.iter_mut() {
         if viewshed.dirty {
             viewshed.dirty = false;
-            let mut visible_tiles = Vec::new();
-            shadow_casting_fov(&*map, (pos.x, pos.y), viewshed.range, |point| {
-                visible_tiles.push(point)
-            });
-            viewshed.visible_tiles = visible_tiles;
+            shadow_casting_fov_into(
+                &*map,
+                Point::new(pos.x, pos.y),
+                viewshed.range,
+                &mut viewshed.visible_tiles,
+            );
+            lighting.retain_lit(&mut viewshed.visible_tiles);
 
             if player.is_some() {
                 for t in map.visible_tiles.iter_mut() {
                     *t = false;
                 }
-                for &(x, y) in viewshed.visible_tiles.iter() {
+                for Point { x, y } in viewshed.visible_tiles.iter() {
                     let idx = map.xy_idx(x, y);
                     map.revealed_tiles[idx] = true;
                     map.visible_tiles[idx] = true;
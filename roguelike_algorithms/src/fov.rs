@@ -4,12 +4,14 @@
 
 mod ray_casting;
 mod shadow_casting;
+mod visibility_grid;
 
-use crate::Point;
-pub use ray_casting::ray_casting_fov;
-pub use shadow_casting::shadow_casting_fov;
+use crate::{Coord, Point};
+pub use ray_casting::{has_line_of_sight, ray_casting_fov, ray_casting_fov_into};
+pub use shadow_casting::{shadow_casting_fov, shadow_casting_fov_into};
+pub use visibility_grid::VisibilityGrid;
 
-pub trait Map2D {
-    fn is_opaque(&self, point: Point) -> bool;
-    fn is_in_bound(&self, point: Point) -> bool;
+pub trait Map2D<T: Coord = i16> {
+    fn is_opaque(&self, point: Point<T>) -> bool;
+    fn is_in_bound(&self, point: Point<T>) -> bool;
 }
@@ -1,16 +1,17 @@
 use crate::{
-    fov::Map2D,
+    fov::{Map2D, VisibilityGrid},
     geometry::{BresenhamCircleNoDiag, BresenhamLine},
-    Point,
+    Coord, Point,
 };
 
-fn scan_fov_line<'a, MAP>(
+fn scan_fov_line<'a, T, MAP>(
     map: &'a MAP,
-    start: Point,
-    end: Point,
-) -> impl Iterator<Item = Point> + 'a
+    start: Point<T>,
+    end: Point<T>,
+) -> impl Iterator<Item = Point<T>> + 'a
 where
-    MAP: Map2D + ?Sized,
+    T: Coord + 'a,
+    MAP: Map2D<T> + ?Sized,
 {
     let mut visible = true;
     BresenhamLine::new(start, end).take_while(move |&point| {
@@ -23,10 +24,11 @@ where
     })
 }
 
-pub fn ray_casting_fov<MAP, F>(map: &MAP, start: Point, range: i16, mut callback: F)
+pub fn ray_casting_fov<T, MAP, F>(map: &MAP, start: Point<T>, range: T, mut callback: F)
 where
-    MAP: Map2D + ?Sized,
-    F: FnMut(Point),
+    T: Coord,
+    MAP: Map2D<T> + ?Sized,
+    F: FnMut(Point<T>),
 {
     callback(start);
 
@@ -34,3 +36,32 @@ where
         scan_fov_line(map, start, point).for_each(&mut callback);
     }
 }
+
+/// Whether `end` can be seen from `start`, i.e., whether the line between
+/// them is in bound and not blocked before reaching `end`.
+///
+/// Useful for restricting an area of effect to what the caster can see.
+pub fn has_line_of_sight<T, MAP>(map: &MAP, start: Point<T>, end: Point<T>) -> bool
+where
+    T: Coord,
+    MAP: Map2D<T> + ?Sized,
+{
+    start == end || scan_fov_line(map, start, end).last() == Some(end)
+}
+
+/// Like [`ray_casting_fov`], but writes the visible points into `grid`,
+/// clearing it first. Doesn't allocate.
+pub fn ray_casting_fov_into<T, MAP>(
+    map: &MAP,
+    start: Point<T>,
+    range: T,
+    grid: &mut VisibilityGrid<T>,
+) where
+    T: Coord,
+    MAP: Map2D<T> + ?Sized,
+{
+    grid.clear();
+    ray_casting_fov(map, start, range, |point| {
+        grid.insert(point);
+    });
+}
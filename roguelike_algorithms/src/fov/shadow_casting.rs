@@ -1,25 +1,32 @@
-use crate::{fov::Map2D, geometry::Octant, Point};
+use crate::{
+    fov::{Map2D, VisibilityGrid},
+    geometry::Octant,
+    Coord, Point,
+};
 
 /// Represents the slope Y/X as a rational number.
+///
+/// Stored as `i64` so that `x * 2 + 1` and friends never overflow.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Slope {
-    pub x: i16,
-    pub y: i16,
+    pub x: i64,
+    pub y: i64,
 }
 
-#[allow(clippy::clippy::too_many_arguments)]
-fn shadow_casting_octant<MAP, F>(
+#[allow(clippy::too_many_arguments)]
+fn shadow_casting_octant<T, MAP, F>(
     map: &MAP,
     octant: Octant,
-    start: Point,
-    range: i16,
-    x: i16,
+    start: Point<T>,
+    range: i64,
+    x: i64,
     mut top: Slope,
     mut bottom: Slope,
     callback: &mut F,
 ) where
-    MAP: Map2D + ?Sized,
-    F: FnMut(Point),
+    T: Coord,
+    MAP: Map2D<T> + ?Sized,
+    F: FnMut(Point<T>),
 {
     for x in x..=range {
         let top_y = if top.x == 1 {
@@ -27,7 +34,7 @@ fn shadow_casting_octant<MAP, F>(
         } else {
             ((x * 2 + 1) * top.y + top.x - 1) / (top.x * 2)
         }
-        .min(((range * range - x * x) as f32).sqrt() as i16);
+        .min(((range * range - x * x) as f64).sqrt() as i64);
         let bottom_y = if bottom.y == 0 {
             0
         } else {
@@ -37,15 +44,20 @@ fn shadow_casting_octant<MAP, F>(
         let mut was_opaque: Option<bool> = None;
 
         for y in bottom_y..=top_y {
-            let point = octant.from_octant0(start, (x, y));
+            // Points that don't fit in `T` are treated as out of bound.
+            let point = octant
+                .checked_from_octant0(start, Point::new(x, y))
+                .filter(|&point| map.is_in_bound(point));
 
-            let in_range = map.is_in_bound(point);
+            let in_range = point.is_some();
 
-            if in_range {
-                callback(point);
-            }
-
-            let is_opaque = !in_range || map.is_opaque(point);
+            let is_opaque = match point {
+                Some(point) => {
+                    callback(point);
+                    map.is_opaque(point)
+                }
+                None => true,
+            };
 
             if x != range {
                 if is_opaque {
@@ -89,10 +101,11 @@ fn shadow_casting_octant<MAP, F>(
     }
 }
 
-pub fn shadow_casting_fov<MAP, F>(map: &MAP, start: Point, range: i16, mut callback: F)
+pub fn shadow_casting_fov<T, MAP, F>(map: &MAP, start: Point<T>, range: T, mut callback: F)
 where
-    MAP: Map2D + ?Sized,
-    F: FnMut(Point),
+    T: Coord,
+    MAP: Map2D<T> + ?Sized,
+    F: FnMut(Point<T>),
 {
     callback(start);
 
@@ -101,7 +114,7 @@ where
             map,
             octant,
             start,
-            range,
+            range.into(),
             1,
             Slope { x: 1, y: 1 },
             Slope { x: 1, y: 0 },
@@ -109,3 +122,20 @@ where
         );
     }
 }
+
+/// Like [`shadow_casting_fov`], but writes the visible points into `grid`,
+/// clearing it first. Doesn't allocate.
+pub fn shadow_casting_fov_into<T, MAP>(
+    map: &MAP,
+    start: Point<T>,
+    range: T,
+    grid: &mut VisibilityGrid<T>,
+) where
+    T: Coord,
+    MAP: Map2D<T> + ?Sized,
+{
+    grid.clear();
+    shadow_casting_fov(map, start, range, |point| {
+        grid.insert(point);
+    });
+}
@@ -0,0 +1,207 @@
+use crate::{Coord, Point};
+
+const BITS: usize = 64;
+
+/// A set of points in a rectangular area, stored as a bitset.
+///
+/// Used to collect the result of a field of view without duplicates.
+/// Points outside the area are never contained, and inserting them does
+/// nothing.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct VisibilityGrid<T = i16> {
+    min: Point<T>,
+    width: usize,
+    height: usize,
+    bits: Vec<u64>,
+}
+
+impl<T: Coord> VisibilityGrid<T> {
+    /// Creates an empty grid covering `(0, 0)` to `(width - 1, height - 1)`.
+    #[inline]
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::with_bounds(Point::ZERO, width, height)
+    }
+
+    /// Creates an empty grid covering `min` to
+    /// `(min.x + width - 1, min.y + height - 1)`.
+    #[inline]
+    pub fn with_bounds(min: Point<T>, width: usize, height: usize) -> Self {
+        VisibilityGrid {
+            min,
+            width,
+            height,
+            bits: vec![0; (width * height).div_ceil(BITS)],
+        }
+    }
+
+    #[inline]
+    pub fn min(&self) -> Point<T> {
+        self.min
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The index of `point` in a row-by-row array over the grid's bounds,
+    /// or `None` if it is outside them.
+    #[inline]
+    pub(crate) fn index(&self, point: Point<T>) -> Option<usize> {
+        let d = point.to_wide() - self.min.to_wide();
+        if d.x < 0 || d.y < 0 || d.x as usize >= self.width || d.y as usize >= self.height {
+            None
+        } else {
+            Some(d.y as usize * self.width + d.x as usize)
+        }
+    }
+
+    #[inline]
+    fn point(&self, index: usize) -> Point<T> {
+        let d = Point::new((index % self.width) as i64, (index / self.width) as i64);
+        // Every index comes from a point in bound, so this never fails.
+        self.min.checked_offset(d).unwrap()
+    }
+
+    #[inline]
+    fn same_bounds(&self, other: &Self) -> bool {
+        self.min == other.min && self.width == other.width && self.height == other.height
+    }
+
+    #[inline]
+    pub fn contains(&self, point: Point<T>) -> bool {
+        match self.index(point) {
+            Some(i) => self.bits[i / BITS] & (1 << (i % BITS)) != 0,
+            None => false,
+        }
+    }
+
+    /// Adds a point to the grid. Returns whether it was newly inserted.
+    #[inline]
+    pub fn insert(&mut self, point: Point<T>) -> bool {
+        match self.index(point) {
+            Some(i) => {
+                let word = &mut self.bits[i / BITS];
+                let mask = 1 << (i % BITS);
+                let inserted = *word & mask == 0;
+                *word |= mask;
+                inserted
+            }
+            None => false,
+        }
+    }
+
+    /// Removes a point from the grid. Returns whether it was present.
+    #[inline]
+    pub fn remove(&mut self, point: Point<T>) -> bool {
+        match self.index(point) {
+            Some(i) => {
+                let word = &mut self.bits[i / BITS];
+                let mask = 1 << (i % BITS);
+                let removed = *word & mask != 0;
+                *word &= !mask;
+                removed
+            }
+            None => false,
+        }
+    }
+
+    /// Removes all points, keeping the bounds and the allocation.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.bits.iter_mut().for_each(|word| *word = 0);
+    }
+
+    /// The number of points in the grid.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|&word| word == 0)
+    }
+
+    /// An iterator over all points in the grid, row by row.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = Point<T>> + '_ {
+        self.bits
+            .iter()
+            .enumerate()
+            .flat_map(|(n, &word)| {
+                let mut word = word;
+                std::iter::from_fn(move || {
+                    if word == 0 {
+                        None
+                    } else {
+                        let bit = word.trailing_zeros() as usize;
+                        word &= word - 1;
+                        Some(n * BITS + bit)
+                    }
+                })
+            })
+            .map(move |i| self.point(i))
+    }
+
+    /// Adds all points of `other`. Points outside the bounds of `self` are
+    /// ignored.
+    pub fn union_with(&mut self, other: &Self) {
+        if self.same_bounds(other) {
+            for (word, &other_word) in self.bits.iter_mut().zip(other.bits.iter()) {
+                *word |= other_word;
+            }
+        } else {
+            for point in other.iter() {
+                self.insert(point);
+            }
+        }
+    }
+
+    /// Removes all points that are not in `other`.
+    pub fn intersect_with(&mut self, other: &Self) {
+        if self.same_bounds(other) {
+            for (word, &other_word) in self.bits.iter_mut().zip(other.bits.iter()) {
+                *word &= other_word;
+            }
+        } else {
+            let removed: Vec<_> = self.iter().filter(|&p| !other.contains(p)).collect();
+            for point in removed {
+                self.remove(point);
+            }
+        }
+    }
+
+    /// Returns the points in either grid, with the bounds of `self`.
+    #[inline]
+    pub fn union(&self, other: &Self) -> Self {
+        let mut grid = self.clone();
+        grid.union_with(other);
+        grid
+    }
+
+    /// Returns the points in both grids, with the bounds of `self`.
+    #[inline]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut grid = self.clone();
+        grid.intersect_with(other);
+        grid
+    }
+}
+
+impl<T: Coord> Extend<Point<T>> for VisibilityGrid<T> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = Point<T>>>(&mut self, iter: I) {
+        for point in iter {
+            self.insert(point);
+        }
+    }
+}
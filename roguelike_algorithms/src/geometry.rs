@@ -1,8 +1,10 @@
+mod area;
 mod circle;
 mod line;
 mod octant;
 mod quadrant;
 
+pub use area::{Cone, Disc, Rectangle, Ring, RotatedRectangle};
 pub use circle::{BresenhamCircle, BresenhamCircleNoDiag};
 pub use line::BresenhamLine;
 pub use octant::Octant;
@@ -0,0 +1,272 @@
+//! Iterators over filled areas: discs, rings, rectangles and cones.
+//!
+//! All of them yield points row by row, and skip points that don't fit in
+//! `T`. To respect walls, filter them with
+//! [`has_line_of_sight`](crate::fov::has_line_of_sight), or intersect them
+//! with a field of view.
+
+use crate::{Coord, Point};
+
+/// Scans the offsets in a bounding box row by row.
+#[derive(Clone, Debug)]
+struct BoxScan {
+    min: Point<i64>,
+    max: Point<i64>,
+    next: Point<i64>,
+}
+
+impl BoxScan {
+    #[inline]
+    fn new(min: Point<i64>, max: Point<i64>) -> BoxScan {
+        BoxScan {
+            min,
+            max,
+            next: min,
+        }
+    }
+
+    #[inline]
+    fn next_offset(&mut self) -> Option<Point<i64>> {
+        if self.next.y > self.max.y || self.min.x > self.max.x {
+            return None;
+        }
+
+        let d = self.next;
+        if self.next.x == self.max.x {
+            self.next.x = self.min.x;
+            self.next.y += 1;
+        } else {
+            self.next.x += 1;
+        }
+
+        Some(d)
+    }
+
+    /// Returns the next offset that satisfies `contains` and fits in `T`
+    /// when added to `origin`.
+    #[inline]
+    fn next_point<T: Coord>(
+        &mut self,
+        origin: Point<T>,
+        contains: impl Fn(Point<i64>) -> bool,
+    ) -> Option<Point<T>> {
+        loop {
+            let d = self.next_offset()?;
+            if contains(d) {
+                if let Some(point) = origin.checked_offset(d) {
+                    return Some(point);
+                }
+            }
+        }
+    }
+}
+
+/// Filled disc iterator
+///
+/// Yields every point whose euclidean distance to `center` is at most `r`,
+/// the same criterion as the shadow casting field of view.
+#[derive(Clone, Debug)]
+pub struct Disc<T = i16> {
+    center: Point<T>,
+    r2: i64,
+    scan: BoxScan,
+}
+
+impl<T: Coord> Disc<T> {
+    #[inline]
+    pub fn new(center: Point<T>, r: T) -> Disc<T> {
+        let r = r.into();
+        Disc {
+            center,
+            r2: r * r,
+            scan: BoxScan::new(Point::new(-r, -r), Point::new(r, r)),
+        }
+    }
+}
+
+impl<T: Coord> Iterator for Disc<T> {
+    type Item = Point<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let r2 = self.r2;
+        self.scan
+            .next_point(self.center, |d| d.x * d.x + d.y * d.y <= r2)
+    }
+}
+
+/// Ring (annulus) iterator
+///
+/// Yields every point whose euclidean distance to `center` is greater than
+/// `inner` and at most `outer`. So `Ring::new(center, 0, r)` is a disc
+/// without its center.
+#[derive(Clone, Debug)]
+pub struct Ring<T = i16> {
+    center: Point<T>,
+    inner2: i64,
+    outer2: i64,
+    scan: BoxScan,
+}
+
+impl<T: Coord> Ring<T> {
+    #[inline]
+    pub fn new(center: Point<T>, inner: T, outer: T) -> Ring<T> {
+        let inner = inner.into();
+        let outer = outer.into();
+        Ring {
+            center,
+            inner2: inner * inner,
+            outer2: outer * outer,
+            scan: BoxScan::new(Point::new(-outer, -outer), Point::new(outer, outer)),
+        }
+    }
+}
+
+impl<T: Coord> Iterator for Ring<T> {
+    type Item = Point<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let inner2 = self.inner2;
+        let outer2 = self.outer2;
+        self.scan.next_point(self.center, |d| {
+            let d2 = d.x * d.x + d.y * d.y;
+            inner2 < d2 && d2 <= outer2
+        })
+    }
+}
+
+/// Axis-aligned filled rectangle iterator
+#[derive(Clone, Debug)]
+pub struct Rectangle<T = i16> {
+    corner: Point<T>,
+    scan: BoxScan,
+}
+
+impl<T: Coord> Rectangle<T> {
+    /// Creates a new iterator. Yields all points between the two corners,
+    /// both included.
+    #[inline]
+    pub fn from_corners(a: Point<T>, b: Point<T>) -> Rectangle<T> {
+        let corner = Point::new(a.x.min(b.x), a.y.min(b.y));
+        let size = Point::new(a.x.max(b.x), a.y.max(b.y)).to_wide() - corner.to_wide();
+        Rectangle {
+            corner,
+            scan: BoxScan::new(Point::ZERO, size),
+        }
+    }
+}
+
+impl<T: Coord> Iterator for Rectangle<T> {
+    type Item = Point<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.scan.next_point(self.corner, |_| true)
+    }
+}
+
+/// Rotated filled rectangle iterator
+///
+/// The rectangle is a thick line: its center line goes from `start` to
+/// `end`, and it extends `half_width` on each side. Useful for beams and
+/// lightning bolts.
+#[derive(Clone, Debug)]
+pub struct RotatedRectangle<T = i16> {
+    start: Point<T>,
+    axis: Point<i64>,
+    half_width: f64,
+    scan: BoxScan,
+}
+
+impl<T: Coord> RotatedRectangle<T> {
+    #[inline]
+    pub fn new(start: Point<T>, end: Point<T>, half_width: f64) -> RotatedRectangle<T> {
+        let axis = end.to_wide() - start.to_wide();
+        let half_width = half_width.max(0.0);
+        let margin = half_width.ceil() as i64;
+        let min = Point::new(axis.x.min(0) - margin, axis.y.min(0) - margin);
+        let max = Point::new(axis.x.max(0) + margin, axis.y.max(0) + margin);
+        RotatedRectangle {
+            start,
+            axis,
+            half_width,
+            scan: BoxScan::new(min, max),
+        }
+    }
+}
+
+impl<T: Coord> Iterator for RotatedRectangle<T> {
+    type Item = Point<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let axis = self.axis;
+        let half_width = self.half_width;
+        let len2 = (axis.x * axis.x + axis.y * axis.y) as f64;
+        self.scan.next_point(self.start, |d| {
+            if len2 == 0.0 {
+                return ((d.x * d.x + d.y * d.y) as f64).sqrt() <= half_width;
+            }
+            let dot = (d.x * axis.x + d.y * axis.y) as f64;
+            let cross = (d.x * axis.y - d.y * axis.x) as f64;
+            (0.0..=len2).contains(&dot) && cross.abs() / len2.sqrt() <= half_width
+        })
+    }
+}
+
+/// Filled cone iterator
+///
+/// Yields every point within `r` of `origin` whose direction is at most
+/// half of `angle` (in radians) away from the direction of `towards`.
+/// `origin` itself is not included.
+#[derive(Clone, Debug)]
+pub struct Cone<T = i16> {
+    origin: Point<T>,
+    direction: (f64, f64),
+    cos_half_angle: f64,
+    r2: i64,
+    scan: BoxScan,
+}
+
+impl<T: Coord> Cone<T> {
+    #[inline]
+    pub fn new(origin: Point<T>, towards: Point<T>, angle: f64, r: T) -> Cone<T> {
+        let d = towards.to_wide() - origin.to_wide();
+        let len = ((d.x * d.x + d.y * d.y) as f64).sqrt();
+        let direction = if len == 0.0 {
+            (1.0, 0.0)
+        } else {
+            (d.x as f64 / len, d.y as f64 / len)
+        };
+        let r = r.into();
+        Cone {
+            origin,
+            direction,
+            cos_half_angle: (angle / 2.0).cos(),
+            r2: r * r,
+            scan: BoxScan::new(Point::new(-r, -r), Point::new(r, r)),
+        }
+    }
+}
+
+impl<T: Coord> Iterator for Cone<T> {
+    type Item = Point<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (dir_x, dir_y) = self.direction;
+        let cos_half_angle = self.cos_half_angle;
+        let r2 = self.r2;
+        self.scan.next_point(self.origin, |d| {
+            let d2 = d.x * d.x + d.y * d.y;
+            if d2 == 0 || d2 > r2 {
+                return false;
+            }
+            let dot = d.x as f64 * dir_x + d.y as f64 * dir_y;
+            // Allow for rounding errors, so that a 90° cone contains
+            // the diagonals.
+            dot / (d2 as f64).sqrt() >= cos_half_angle - 1e-9
+        })
+    }
+}
@@ -4,112 +4,134 @@
 
 use crate::{
     geometry::{Octant, Quadrant},
-    Point,
+    Coord, Point,
 };
 
 /// Circle-drawing iterator
-pub struct BresenhamCircle {
+///
+/// Points that don't fit in `T` are skipped.
+pub struct BresenhamCircle<T = i16> {
     quadrant: Quadrant,
-    point: Point,
-    center: Point,
-    r: i16,
-    err: i16,
+    point: Point<i64>,
+    center: Point<T>,
+    r: i64,
+    err: i64,
 }
 
-impl BresenhamCircle {
+impl<T: Coord> BresenhamCircle<T> {
     #[inline]
-    pub fn new(center: Point, r: i16) -> BresenhamCircle {
+    pub fn new(center: Point<T>, r: T) -> BresenhamCircle<T> {
+        let r = r.into();
         BresenhamCircle {
             quadrant: Quadrant(0),
-            point: (0, r),
+            point: Point::new(0, r),
             center,
             r,
             err: 2 - 2 * r,
         }
     }
-}
-
-impl Iterator for BresenhamCircle {
-    type Item = Point;
 
     #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.point.1 == 0 {
+    fn next_offset(&mut self) -> Option<Point<i64>> {
+        if self.point.y == 0 {
             if self.quadrant.0 == 3 {
                 return None;
             } else {
                 self.quadrant.0 += 1;
-                self.point = (0, self.r);
+                self.point = Point::new(0, self.r);
                 self.err = 2 - 2 * self.r;
             }
         }
 
         let e = self.err;
-        if e <= self.point.0 {
-            self.point.0 += 1;
-            self.err += self.point.0 * 2 + 1;
+        if e <= self.point.x {
+            self.point.x += 1;
+            self.err += self.point.x * 2 + 1;
         }
-        if e > -self.point.1 {
-            self.point.1 -= 1;
-            self.err += -self.point.1 * 2 + 1;
+        if e > -self.point.y {
+            self.point.y -= 1;
+            self.err += -self.point.y * 2 + 1;
         }
 
-        let point = self
-            .quadrant
-            .from_quadrant0_rotating(self.center, self.point);
+        Some(self.quadrant.from_quadrant0_rotating_offset(self.point))
+    }
+}
+
+impl<T: Coord> Iterator for BresenhamCircle<T> {
+    type Item = Point<T>;
 
-        Some(point)
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let d = self.next_offset()?;
+            if let Some(point) = self.center.checked_offset(d) {
+                return Some(point);
+            }
+        }
     }
 }
 
 /// Circle-drawing iterator
-pub struct BresenhamCircleNoDiag {
+///
+/// Points that don't fit in `T` are skipped.
+pub struct BresenhamCircleNoDiag<T = i16> {
     octant: Octant,
-    point: Point,
-    center: Point,
-    r: i16,
-    err: i16,
+    point: Point<i64>,
+    center: Point<T>,
+    r: i64,
+    err: i64,
 }
 
-impl BresenhamCircleNoDiag {
+impl<T: Coord> BresenhamCircleNoDiag<T> {
     #[inline]
-    pub fn new(center: Point, r: i16) -> BresenhamCircleNoDiag {
+    pub fn new(center: Point<T>, r: T) -> BresenhamCircleNoDiag<T> {
+        let r = r.into();
         BresenhamCircleNoDiag {
             octant: Octant(0),
-            point: (0, r),
+            point: Point::new(0, r),
             center,
             r,
             err: 2 - 2 * r,
         }
     }
-}
-
-impl Iterator for BresenhamCircleNoDiag {
-    type Item = Point;
 
     #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        let point = self.octant.from_octant0(self.center, self.point);
+    fn next_offset(&mut self) -> Option<Point<i64>> {
+        let d = self.octant.from_octant0_offset(self.point);
 
-        if self.point.0 == self.point.1 {
+        if self.point.x == self.point.y {
             if self.octant.0 == 7 {
                 return None;
             } else {
                 self.octant.0 += 1;
-                self.point = (0, self.r);
+                self.point = Point::new(0, self.r);
                 self.err = 2 - 2 * self.r;
-                return Some(point);
+                return Some(d);
             }
         }
 
         if self.err > 0 {
-            self.point.1 -= 1;
-            self.err += -self.point.1 * 2 + 1;
+            self.point.y -= 1;
+            self.err += -self.point.y * 2 + 1;
         } else {
-            self.point.0 += 1;
-            self.err += self.point.0 * 2 + 1;
+            self.point.x += 1;
+            self.err += self.point.x * 2 + 1;
         }
 
-        Some(point)
+        Some(d)
+    }
+}
+
+impl<T: Coord> Iterator for BresenhamCircleNoDiag<T> {
+    type Item = Point<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let d = self.next_offset()?;
+            if let Some(point) = self.center.checked_offset(d) {
+                return Some(point);
+            }
+        }
     }
 }
@@ -2,27 +2,27 @@
 //!
 //! Based on http://members.chello.at/~easyfilter/bresenham.html
 
-use crate::{geometry::Quadrant, Point};
+use crate::{geometry::Quadrant, Coord, Point};
 
 /// Line-drawing iterator
-pub struct BresenhamLine {
-    point: Point,
-    end: Point,
-    dx: i16,
-    dy: i16,
-    sx: i16,
-    sy: i16,
-    err: i16,
+pub struct BresenhamLine<T = i16> {
+    point: Point<T>,
+    end: Point<T>,
+    dx: i64,
+    dy: i64,
+    sx: T,
+    sy: T,
+    err: i64,
 }
 
-impl BresenhamLine {
+impl<T: Coord> BresenhamLine<T> {
     /// Creates a new iterator. Yields intermediate points between `start`
     /// and `end`. Does include `end` but not `start`.
     #[inline]
-    pub fn new(start: Point, end: Point) -> BresenhamLine {
-        let (quadrant, (dx, dy)) = Quadrant::from_points(start, end);
+    pub fn new(start: Point<T>, end: Point<T>) -> BresenhamLine<T> {
+        let (quadrant, Point { x: dx, y: dy }) = Quadrant::from_points(start, end);
 
-        let (sx, sy) = quadrant.from_quadrant0((0, 0), (1, 1));
+        let Point { x: sx, y: sy } = quadrant.from_quadrant0_offset(Point::new(T::ONE, T::ONE));
 
         BresenhamLine {
             point: start,
@@ -36,8 +36,8 @@ impl BresenhamLine {
     }
 }
 
-impl Iterator for BresenhamLine {
-    type Item = Point;
+impl<T: Coord> Iterator for BresenhamLine<T> {
+    type Item = Point<T>;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
@@ -47,11 +47,11 @@ impl Iterator for BresenhamLine {
 
         let e2 = 2 * self.err;
         if e2 >= -self.dy {
-            self.point.0 += self.sx;
+            self.point.x += self.sx;
             self.err -= self.dy;
         }
         if e2 <= self.dx {
-            self.point.1 += self.sy;
+            self.point.y += self.sy;
             self.err += self.dx;
         }
 
@@ -1,14 +1,19 @@
-use crate::Point;
+use crate::{Coord, Point};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Octant(pub u8);
 
 /// adapted from http://codereview.stackexchange.com/a/95551
 impl Octant {
+    /// Returns the octant of `end` relative to `start`, and the offset
+    /// between them transformed into octant 0.
+    ///
+    /// The offset is computed in `i64`, so it never overflows.
     #[inline]
-    pub fn from_points(start: Point, end: Point) -> (Octant, Point) {
-        let mut dx = end.0 - start.0;
-        let mut dy = end.1 - start.1;
+    pub fn from_points<T: Coord>(start: Point<T>, end: Point<T>) -> (Octant, Point<i64>) {
+        let d = end.to_wide() - start.to_wide();
+        let mut dx = d.x;
+        let mut dy = d.y;
 
         let mut octant = 0;
 
@@ -29,38 +34,58 @@ impl Octant {
             octant += 1;
         }
 
-        (Octant(octant), (dx, dy))
+        (Octant(octant), Point::new(dx, dy))
     }
 
     #[inline]
-    pub fn to_octant0(&self, p: Point) -> Point {
+    pub fn to_octant0<T: Coord>(&self, p: Point<T>) -> Point<T> {
         match self.0 {
-            0 => (p.0, p.1),
-            1 => (p.1, p.0),
-            2 => (p.1, -p.0),
-            3 => (-p.0, p.1),
-            4 => (-p.0, -p.1),
-            5 => (-p.1, -p.0),
-            6 => (-p.1, p.0),
-            7 => (p.0, -p.1),
+            0 => Point::new(p.x, p.y),
+            1 => Point::new(p.y, p.x),
+            2 => Point::new(p.y, -p.x),
+            3 => Point::new(-p.x, p.y),
+            4 => Point::new(-p.x, -p.y),
+            5 => Point::new(-p.y, -p.x),
+            6 => Point::new(-p.y, p.x),
+            7 => Point::new(p.x, -p.y),
             _ => unreachable!(),
         }
     }
 
+    /// Transforms an offset in octant 0 into this octant.
+    ///
+    /// Like negating an integer, it overflows if a coordinate of `d` is
+    /// `T::MIN`. Offsets in octant 0 are never negative, so they are fine.
     #[inline]
-    pub fn from_octant0(&self, start: Point, d: Point) -> Point {
-        let (dx, dy) = match self.0 {
-            0 => (d.0, d.1),
-            1 => (d.1, d.0),
-            2 => (-d.1, d.0),
-            3 => (-d.0, d.1),
-            4 => (-d.0, -d.1),
-            5 => (-d.1, -d.0),
-            6 => (d.1, -d.0),
-            7 => (d.0, -d.1),
+    pub fn from_octant0_offset<T: Coord>(&self, d: Point<T>) -> Point<T> {
+        match self.0 {
+            0 => Point::new(d.x, d.y),
+            1 => Point::new(d.y, d.x),
+            2 => Point::new(-d.y, d.x),
+            3 => Point::new(-d.x, d.y),
+            4 => Point::new(-d.x, -d.y),
+            5 => Point::new(-d.y, -d.x),
+            6 => Point::new(d.y, -d.x),
+            7 => Point::new(d.x, -d.y),
             _ => unreachable!(),
-        };
-        (start.0 + dx, start.1 + dy)
+        }
+    }
+
+    /// Adds an offset in octant 0, transformed into this octant, to
+    /// `start`. Returns `None` if the result doesn't fit in `T`.
+    #[inline]
+    pub fn from_octant0<T: Coord>(&self, start: Point<T>, d: Point<T>) -> Option<Point<T>> {
+        self.checked_from_octant0(start, d.to_wide())
+    }
+
+    /// Like `from_octant0`, but takes an `i64` offset.
+    #[inline]
+    pub fn checked_from_octant0<T: Coord>(
+        &self,
+        start: Point<T>,
+        d: Point<i64>,
+    ) -> Option<Point<T>> {
+        start.checked_offset(self.from_octant0_offset(d))
     }
 
     #[inline]
@@ -1,14 +1,19 @@
-pub use crate::Point;
+pub use crate::{Coord, Point};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Quadrant(pub u8);
 
 /// adapted from http://codereview.stackexchange.com/a/95551
 impl Quadrant {
+    /// Returns the quadrant of `end` relative to `start`, and the offset
+    /// between them transformed into quadrant 0.
+    ///
+    /// The offset is computed in `i64`, so it never overflows.
     #[inline]
-    pub fn from_points(start: Point, end: Point) -> (Quadrant, Point) {
-        let mut dx = end.0 - start.0;
-        let mut dy = end.1 - start.1;
+    pub fn from_points<T: Coord>(start: Point<T>, end: Point<T>) -> (Quadrant, Point<i64>) {
+        let d = end.to_wide() - start.to_wide();
+        let mut dx = d.x;
+        let mut dy = d.y;
 
         let mut quadrant = 0;
 
@@ -23,13 +28,17 @@ impl Quadrant {
             quadrant += 1;
         }
 
-        (Quadrant(quadrant), (dx, dy))
+        (Quadrant(quadrant), Point::new(dx, dy))
     }
 
     #[inline]
-    pub fn from_points_rotating(start: Point, end: Point) -> (Quadrant, Point) {
-        let mut dx = end.0 - start.0;
-        let mut dy = end.1 - start.1;
+    pub fn from_points_rotating<T: Coord>(
+        start: Point<T>,
+        end: Point<T>,
+    ) -> (Quadrant, Point<i64>) {
+        let d = end.to_wide() - start.to_wide();
+        let mut dx = d.x;
+        let mut dy = d.y;
 
         let mut quadrant = 0;
 
@@ -45,53 +54,75 @@ impl Quadrant {
             quadrant += 1;
         }
 
-        (Quadrant(quadrant), (dx, dy))
+        (Quadrant(quadrant), Point::new(dx, dy))
     }
 
     #[inline]
-    pub fn to_quadrant0(&self, p: Point) -> Point {
+    pub fn to_quadrant0<T: Coord>(&self, p: Point<T>) -> Point<T> {
         match self.0 {
-            0 => (p.0, p.1),
-            1 => (-p.0, p.1),
-            2 => (-p.0, -p.1),
-            3 => (p.0, -p.1),
+            0 => Point::new(p.x, p.y),
+            1 => Point::new(-p.x, p.y),
+            2 => Point::new(-p.x, -p.y),
+            3 => Point::new(p.x, -p.y),
             _ => unreachable!(),
         }
     }
 
     #[inline]
-    pub fn to_quadrant0_rotating(&self, p: Point) -> Point {
+    pub fn to_quadrant0_rotating<T: Coord>(&self, p: Point<T>) -> Point<T> {
         match self.0 {
-            0 => (p.0, p.1),
-            1 => (p.1, -p.0),
-            2 => (-p.0, -p.1),
-            3 => (-p.1, p.0),
+            0 => Point::new(p.x, p.y),
+            1 => Point::new(p.y, -p.x),
+            2 => Point::new(-p.x, -p.y),
+            3 => Point::new(-p.y, p.x),
             _ => unreachable!(),
         }
     }
 
+    /// Transforms an offset in quadrant 0 into this quadrant.
+    ///
+    /// Like negating an integer, it overflows if a coordinate of `d` is
+    /// `T::MIN`. Offsets in quadrant 0 are never negative, so they are
+    /// fine.
     #[inline]
-    pub fn from_quadrant0(&self, start: Point, d: Point) -> Point {
-        let (dx, dy) = match self.0 {
-            0 => (d.0, d.1),
-            1 => (-d.0, d.1),
-            2 => (-d.0, -d.1),
-            3 => (d.0, -d.1),
+    pub fn from_quadrant0_offset<T: Coord>(&self, d: Point<T>) -> Point<T> {
+        match self.0 {
+            0 => Point::new(d.x, d.y),
+            1 => Point::new(-d.x, d.y),
+            2 => Point::new(-d.x, -d.y),
+            3 => Point::new(d.x, -d.y),
             _ => unreachable!(),
-        };
-        (start.0 + dx, start.1 + dy)
+        }
     }
 
+    /// Transforms an offset in quadrant 0 into this quadrant, rotating
+    /// instead of reflecting.
     #[inline]
-    pub fn from_quadrant0_rotating(&self, start: Point, d: Point) -> Point {
-        let (dx, dy) = match self.0 {
-            0 => (d.0, d.1),
-            1 => (-d.1, d.0),
-            2 => (-d.0, -d.1),
-            3 => (d.1, -d.0),
+    pub fn from_quadrant0_rotating_offset<T: Coord>(&self, d: Point<T>) -> Point<T> {
+        match self.0 {
+            0 => Point::new(d.x, d.y),
+            1 => Point::new(-d.y, d.x),
+            2 => Point::new(-d.x, -d.y),
+            3 => Point::new(d.y, -d.x),
             _ => unreachable!(),
-        };
-        (start.0 + dx, start.1 + dy)
+        }
+    }
+
+    /// Adds an offset in quadrant 0, transformed into this quadrant, to
+    /// `start`. Returns `None` if the result doesn't fit in `T`.
+    #[inline]
+    pub fn from_quadrant0<T: Coord>(&self, start: Point<T>, d: Point<T>) -> Option<Point<T>> {
+        start.checked_offset(self.from_quadrant0_offset(d.to_wide()))
+    }
+
+    /// Like `from_quadrant0`, rotating instead of reflecting.
+    #[inline]
+    pub fn from_quadrant0_rotating<T: Coord>(
+        &self,
+        start: Point<T>,
+        d: Point<T>,
+    ) -> Option<Point<T>> {
+        start.checked_offset(self.from_quadrant0_rotating_offset(d.to_wide()))
     }
 
     #[inline]
@@ -1,4 +1,8 @@
 pub mod fov;
 pub mod geometry;
+pub mod lighting;
+pub mod pathfinding;
+pub mod sound;
+mod point;
 
-pub type Point = (i16, i16);
+pub use point::{Coord, IVec2, Point};
@@ -0,0 +1,210 @@
+//! Light sources and light-level propagation.
+//!
+//! Light spreads from each source using shadow casting, so opaque cells
+//! cast shadows. The light of overlapping sources adds up.
+
+use crate::{
+    fov::{shadow_casting_fov_into, Map2D, VisibilityGrid},
+    Coord, Point,
+};
+use std::ops::{Add, AddAssign, Mul};
+
+/// A colour with floating point channels, usually between `0.0` and `1.0`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rgb {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb::new(0.0, 0.0, 0.0);
+    pub const WHITE: Rgb = Rgb::new(1.0, 1.0, 1.0);
+
+    #[inline]
+    pub const fn new(r: f32, g: f32, b: f32) -> Rgb {
+        Rgb { r, g, b }
+    }
+
+    /// Creates a colour from 8-bit channels.
+    #[inline]
+    pub fn from_u8(r: u8, g: u8, b: u8) -> Rgb {
+        Rgb::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0)
+    }
+
+    /// Converts to 8-bit channels, clamping each channel to `0.0..=1.0`.
+    #[inline]
+    pub fn to_u8(self) -> (u8, u8, u8) {
+        let convert = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+        (convert(self.r), convert(self.g), convert(self.b))
+    }
+
+    /// The brightest channel.
+    #[inline]
+    pub fn intensity(self) -> f32 {
+        self.r.max(self.g).max(self.b)
+    }
+}
+
+impl Add for Rgb {
+    type Output = Rgb;
+
+    #[inline]
+    fn add(self, rhs: Rgb) -> Rgb {
+        Rgb::new(self.r + rhs.r, self.g + rhs.g, self.b + rhs.b)
+    }
+}
+
+impl AddAssign for Rgb {
+    #[inline]
+    fn add_assign(&mut self, rhs: Rgb) {
+        *self = *self + rhs;
+    }
+}
+
+/// Scales every channel.
+impl Mul<f32> for Rgb {
+    type Output = Rgb;
+
+    #[inline]
+    fn mul(self, rhs: f32) -> Rgb {
+        Rgb::new(self.r * rhs, self.g * rhs, self.b * rhs)
+    }
+}
+
+/// Multiplies channel by channel, e.g., to tint a tile colour by a light.
+impl Mul for Rgb {
+    type Output = Rgb;
+
+    #[inline]
+    fn mul(self, rhs: Rgb) -> Rgb {
+        Rgb::new(self.r * rhs.r, self.g * rhs.g, self.b * rhs.b)
+    }
+}
+
+/// How the light of a source fades with the distance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Falloff {
+    /// Full brightness up to the radius.
+    None,
+    /// Fades linearly to zero just beyond the radius.
+    Linear,
+    /// Fades quadratically to zero just beyond the radius: bright near
+    /// the source, dim near the edge.
+    Quadratic,
+}
+
+impl Falloff {
+    /// The factor of the light at `distance` from a source of `radius`.
+    #[inline]
+    pub fn factor(self, distance: f32, radius: f32) -> f32 {
+        let t = (1.0 - distance / (radius + 1.0)).max(0.0);
+        match self {
+            Falloff::None => 1.0,
+            Falloff::Linear => t,
+            Falloff::Quadratic => t * t,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LightSource<T = i16> {
+    pub position: Point<T>,
+    pub radius: T,
+    pub color: Rgb,
+    pub falloff: Falloff,
+}
+
+/// The light level of every cell in a rectangular area.
+#[derive(Clone, Debug)]
+pub struct LightMap<T = i16> {
+    cells: Vec<Rgb>,
+    /// The cells lit by a source, with the bounds of the light map.
+    scratch: VisibilityGrid<T>,
+}
+
+impl<T: Coord> LightMap<T> {
+    /// Creates a dark light map covering `(0, 0)` to
+    /// `(width - 1, height - 1)`.
+    #[inline]
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::with_bounds(Point::ZERO, width, height)
+    }
+
+    /// Creates a dark light map covering `min` to
+    /// `(min.x + width - 1, min.y + height - 1)`.
+    #[inline]
+    pub fn with_bounds(min: Point<T>, width: usize, height: usize) -> Self {
+        LightMap {
+            cells: vec![Rgb::BLACK; width * height],
+            scratch: VisibilityGrid::with_bounds(min, width, height),
+        }
+    }
+
+    /// Sets every cell to the ambient light.
+    #[inline]
+    pub fn fill(&mut self, ambient: Rgb) {
+        self.cells.iter_mut().for_each(|cell| *cell = ambient);
+    }
+
+    /// The colour of the light at `point`. Black outside the bounds.
+    #[inline]
+    pub fn light(&self, point: Point<T>) -> Rgb {
+        self.scratch.index(point).map_or(Rgb::BLACK, |i| self.cells[i])
+    }
+
+    /// The intensity of the light at `point`, i.e., its brightest channel.
+    #[inline]
+    pub fn intensity(&self, point: Point<T>) -> f32 {
+        self.light(point).intensity()
+    }
+
+    /// Whether the light at `point` is brighter than `threshold`.
+    #[inline]
+    pub fn is_lit(&self, point: Point<T>, threshold: f32) -> bool {
+        self.intensity(point) > threshold
+    }
+
+    /// Removes the points that are not lit from `grid`, e.g., to restrict a
+    /// field of view to what can be seen in the dark.
+    pub fn retain_lit(&self, grid: &mut VisibilityGrid<T>, threshold: f32) {
+        let dark: Vec<_> = grid
+            .iter()
+            .filter(|&point| !self.is_lit(point, threshold))
+            .collect();
+        for point in dark {
+            grid.remove(point);
+        }
+    }
+
+    /// Adds the light of a source.
+    pub fn add_light<MAP>(&mut self, map: &MAP, source: &LightSource<T>)
+    where
+        MAP: Map2D<T> + ?Sized,
+    {
+        let radius: i64 = source.radius.into();
+        shadow_casting_fov_into(map, source.position, source.radius, &mut self.scratch);
+
+        for point in self.scratch.iter() {
+            let distance = source.position.euclidean_distance(point) as f32;
+            let factor = source.falloff.factor(distance, radius as f32);
+            if let Some(i) = self.scratch.index(point) {
+                self.cells[i] += source.color * factor;
+            }
+        }
+    }
+
+    /// Recomputes the whole light map from the ambient light and the
+    /// sources.
+    pub fn compute<'a, MAP, I>(&mut self, map: &MAP, ambient: Rgb, sources: I)
+    where
+        T: 'a,
+        MAP: Map2D<T> + ?Sized,
+        I: IntoIterator<Item = &'a LightSource<T>>,
+    {
+        self.fill(ambient);
+        for source in sources {
+            self.add_light(map, source);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Pathfinding.
+//!
+//! A* search on a grid where every cell has a cost to enter, e.g., so that
+//! monsters walk around water instead of wading through it.
+
+use crate::{Coord, Point};
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, BinaryHeap, HashMap},
+};
+
+pub trait PathMap2D<T: Coord = i16> {
+    /// The cost of stepping onto `point`, at least `1`, or `None` if it
+    /// can't be entered, e.g., because it is a wall or out of bound.
+    fn cost(&self, point: Point<T>) -> Option<u32>;
+}
+
+/// Finds the cheapest path from `start` to `goal`, through orthogonal
+/// steps, and also diagonal ones if `diagonal` is true.
+///
+/// The path includes `goal` but not `start`, so it is empty if they are
+/// the same point. `None` if `goal` can't be reached.
+pub fn a_star<T, MAP>(
+    map: &MAP,
+    start: Point<T>,
+    goal: Point<T>,
+    diagonal: bool,
+) -> Option<Vec<Point<T>>>
+where
+    T: Coord,
+    MAP: PathMap2D<T> + ?Sized,
+{
+    // Every step costs at least `1`, so this never overestimates.
+    let heuristic = |point: Point<T>| {
+        if diagonal {
+            point.chebyshev_distance(goal) as u64
+        } else {
+            point.manhattan_distance(goal) as u64
+        }
+    };
+
+    let mut costs = HashMap::new();
+    let mut came_from = HashMap::new();
+    let mut queue = BinaryHeap::new();
+
+    costs.insert(start, 0);
+    queue.push(Reverse((heuristic(start), 0, start)));
+
+    while let Some(Reverse((_, cost, point))) = queue.pop() {
+        if point == goal {
+            let mut path = vec![goal];
+            let mut current = goal;
+            while let Some(&previous) = came_from.get(&current) {
+                if previous == start {
+                    break;
+                }
+                path.push(previous);
+                current = previous;
+            }
+            if goal == start {
+                path.clear();
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        if costs[&point] < cost {
+            continue;
+        }
+
+        let neighbours: Vec<_> = if diagonal {
+            point.neighbours_8().collect()
+        } else {
+            point.neighbours_4().collect()
+        };
+
+        for neighbour in neighbours {
+            let step = match map.cost(neighbour) {
+                Some(step) => step as u64,
+                None => continue,
+            };
+            let new_cost = cost + step;
+            match costs.entry(neighbour) {
+                Entry::Occupied(mut entry) => {
+                    if *entry.get() <= new_cost {
+                        continue;
+                    }
+                    entry.insert(new_cost);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(new_cost);
+                }
+            }
+            came_from.insert(neighbour, point);
+            queue.push(Reverse((new_cost + heuristic(neighbour), new_cost, neighbour)));
+        }
+    }
+
+    None
+}
@@ -0,0 +1,344 @@
+//! A 2D integer point, generic over its coordinate type.
+
+use std::{
+    convert::TryFrom,
+    fmt::Debug,
+    hash::Hash,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+/// Integer types that can be used as coordinates of a [`Point`].
+///
+/// Algorithms in this crate do their intermediate arithmetic in `i64`, and
+/// squared distances in `i128`, so they don't overflow even when the
+/// coordinates are close to the bounds of `i16` or `i32`. With `i64`
+/// coordinates, the points must stay within the bounds of `i32`.
+pub trait Coord:
+    Copy
+    + Debug
+    + Default
+    + Eq
+    + Ord
+    + Hash
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+    + Into<i64>
+    + TryFrom<i64>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const NEG_ONE: Self;
+    const MIN: Self;
+    const MAX: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+
+    /// Converts an `i64` to this type, returning `None` if it doesn't fit.
+    #[inline]
+    fn from_i64(n: i64) -> Option<Self> {
+        Self::try_from(n).ok()
+    }
+}
+
+macro_rules! impl_coord {
+    ($($t:ty),*) => {
+        $(
+            impl Coord for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const NEG_ONE: Self = -1;
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+
+                #[inline]
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_add(self, rhs)
+                }
+
+                #[inline]
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_coord!(i16, i32, i64);
+
+/// A point, or a vector, on a 2D grid.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point<T = i16> {
+    pub x: T,
+    pub y: T,
+}
+
+/// A point with `i32` coordinates, for maps too large for `i16`.
+pub type IVec2 = Point<i32>;
+
+impl<T: Coord> Point<T> {
+    pub const ZERO: Self = Point {
+        x: T::ZERO,
+        y: T::ZERO,
+    };
+    pub const NORTH: Self = Point {
+        x: T::ZERO,
+        y: T::NEG_ONE,
+    };
+    pub const SOUTH: Self = Point {
+        x: T::ZERO,
+        y: T::ONE,
+    };
+    pub const EAST: Self = Point {
+        x: T::ONE,
+        y: T::ZERO,
+    };
+    pub const WEST: Self = Point {
+        x: T::NEG_ONE,
+        y: T::ZERO,
+    };
+    pub const NORTH_EAST: Self = Point {
+        x: T::ONE,
+        y: T::NEG_ONE,
+    };
+    pub const NORTH_WEST: Self = Point {
+        x: T::NEG_ONE,
+        y: T::NEG_ONE,
+    };
+    pub const SOUTH_EAST: Self = Point {
+        x: T::ONE,
+        y: T::ONE,
+    };
+    pub const SOUTH_WEST: Self = Point {
+        x: T::NEG_ONE,
+        y: T::ONE,
+    };
+
+    /// The four orthogonal directions, clockwise from north.
+    pub const DIRECTIONS_4: [Self; 4] = [Self::NORTH, Self::EAST, Self::SOUTH, Self::WEST];
+
+    /// The eight orthogonal and diagonal directions, clockwise from north.
+    pub const DIRECTIONS_8: [Self; 8] = [
+        Self::NORTH,
+        Self::NORTH_EAST,
+        Self::EAST,
+        Self::SOUTH_EAST,
+        Self::SOUTH,
+        Self::SOUTH_WEST,
+        Self::WEST,
+        Self::NORTH_WEST,
+    ];
+
+    #[inline]
+    pub const fn new(x: T, y: T) -> Self {
+        Point { x, y }
+    }
+
+    /// Converts the coordinates to `i64`.
+    #[inline]
+    pub fn to_wide(self) -> Point<i64> {
+        Point::new(self.x.into(), self.y.into())
+    }
+
+    /// Converts a point with `i64` coordinates back to this type,
+    /// returning `None` if it doesn't fit.
+    #[inline]
+    pub fn from_wide(p: Point<i64>) -> Option<Self> {
+        Some(Point::new(T::from_i64(p.x)?, T::from_i64(p.y)?))
+    }
+
+    #[inline]
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        Some(Point::new(
+            self.x.checked_add(rhs.x)?,
+            self.y.checked_add(rhs.y)?,
+        ))
+    }
+
+    #[inline]
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        Some(Point::new(
+            self.x.checked_sub(rhs.x)?,
+            self.y.checked_sub(rhs.y)?,
+        ))
+    }
+
+    /// Adds an offset given in `i64`, returning `None` if the result
+    /// doesn't fit.
+    #[inline]
+    pub fn checked_offset(self, d: Point<i64>) -> Option<Self> {
+        let wide = self.to_wide();
+        Self::from_wide(Point::new(
+            wide.x.checked_add(d.x)?,
+            wide.y.checked_add(d.y)?,
+        ))
+    }
+
+    /// The taxicab distance `|dx| + |dy|`.
+    #[inline]
+    pub fn manhattan_distance(self, other: Self) -> i64 {
+        let d = other.to_wide() - self.to_wide();
+        d.x.abs() + d.y.abs()
+    }
+
+    /// The chessboard distance `max(|dx|, |dy|)`, i.e., the number of
+    /// king moves between two points.
+    #[inline]
+    pub fn chebyshev_distance(self, other: Self) -> i64 {
+        let d = other.to_wide() - self.to_wide();
+        d.x.abs().max(d.y.abs())
+    }
+
+    /// The square of the euclidean distance, in `i128` since it doesn't fit
+    /// in `i64` for points at opposite bounds of `i32`.
+    #[inline]
+    pub fn distance_squared(self, other: Self) -> i128 {
+        let d = other.to_wide() - self.to_wide();
+        let (dx, dy) = (i128::from(d.x), i128::from(d.y));
+        dx * dx + dy * dy
+    }
+
+    #[inline]
+    pub fn euclidean_distance(self, other: Self) -> f64 {
+        (self.distance_squared(other) as f64).sqrt()
+    }
+
+    /// An iterator over the four orthogonal neighbours.
+    ///
+    /// Neighbours that don't fit in `T` are skipped.
+    #[inline]
+    pub fn neighbours_4(self) -> impl Iterator<Item = Self> {
+        (0..4).filter_map(move |i| self.checked_add(Self::DIRECTIONS_4[i]))
+    }
+
+    /// An iterator over the eight orthogonal and diagonal neighbours.
+    ///
+    /// Neighbours that don't fit in `T` are skipped.
+    #[inline]
+    pub fn neighbours_8(self) -> impl Iterator<Item = Self> {
+        (0..8).filter_map(move |i| self.checked_add(Self::DIRECTIONS_8[i]))
+    }
+}
+
+impl<T: Coord> Add for Point<T> {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Point::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl<T: Coord> AddAssign for Point<T> {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+    }
+}
+
+impl<T: Coord> Sub for Point<T> {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Point::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<T: Coord> SubAssign for Point<T> {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+    }
+}
+
+/// Like negating an integer, it overflows if a coordinate is `T::MIN`.
+impl<T: Coord> Neg for Point<T> {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl<T: Coord> Mul<T> for Point<T> {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: T) -> Self {
+        Point::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl<T: Coord> MulAssign<T> for Point<T> {
+    #[inline]
+    fn mul_assign(&mut self, rhs: T) {
+        self.x *= rhs;
+        self.y *= rhs;
+    }
+}
+
+impl<T: Coord> Div<T> for Point<T> {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, rhs: T) -> Self {
+        Point::new(self.x / rhs, self.y / rhs)
+    }
+}
+
+impl<T: Coord> DivAssign<T> for Point<T> {
+    #[inline]
+    fn div_assign(&mut self, rhs: T) {
+        self.x /= rhs;
+        self.y /= rhs;
+    }
+}
+
+impl<T> From<(T, T)> for Point<T> {
+    #[inline]
+    fn from((x, y): (T, T)) -> Self {
+        Point { x, y }
+    }
+}
+
+impl<T> From<Point<T>> for (T, T) {
+    #[inline]
+    fn from(p: Point<T>) -> Self {
+        (p.x, p.y)
+    }
+}
+
+impl<T> From<[T; 2]> for Point<T> {
+    #[inline]
+    fn from([x, y]: [T; 2]) -> Self {
+        Point { x, y }
+    }
+}
+
+impl From<Point<i16>> for Point<i32> {
+    #[inline]
+    fn from(p: Point<i16>) -> Self {
+        Point::new(p.x.into(), p.y.into())
+    }
+}
+
+impl TryFrom<Point<i32>> for Point<i16> {
+    type Error = std::num::TryFromIntError;
+
+    #[inline]
+    fn try_from(p: Point<i32>) -> Result<Self, Self::Error> {
+        Ok(Point::new(i16::try_from(p.x)?, i16::try_from(p.y)?))
+    }
+}
@@ -0,0 +1,155 @@
+//! Noise and sound propagation.
+//!
+//! A noise spreads from its source like a flood fill, losing volume on
+//! every cell it enters, and stops when the volume runs out. It goes
+//! around walls rather than through them, so a listener hears it from the
+//! direction of the shortest path, not from the direction of the source.
+
+use crate::{fov::Map2D, Coord, Point};
+use std::collections::{hash_map::Entry, BinaryHeap, HashMap};
+
+pub trait SoundMap2D<T: Coord = i16>: Map2D<T> {
+    /// How much volume a sound loses when entering `point`, or `None` if
+    /// `point` blocks sound completely.
+    ///
+    /// By default, opaque cells block sound and other cells cost `1`.
+    /// Override it to make doors muffle sound, for example.
+    #[inline]
+    fn attenuation(&self, point: Point<T>) -> Option<u32> {
+        if self.is_opaque(point) {
+            None
+        } else {
+            Some(1)
+        }
+    }
+}
+
+/// How a sound is heard at a cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Heard<T = i16> {
+    /// The remaining volume, always positive.
+    pub volume: u32,
+    /// The neighbouring cell the sound came from. The source itself for
+    /// the source cell.
+    pub from: Point<T>,
+}
+
+/// The cells reached by a sound.
+#[derive(Clone, Debug)]
+pub struct SoundField<T = i16> {
+    source: Point<T>,
+    cells: HashMap<Point<T>, Heard<T>>,
+}
+
+impl<T: Coord> SoundField<T> {
+    #[inline]
+    pub fn source(&self) -> Point<T> {
+        self.source
+    }
+
+    /// How the sound is heard at `point`, or `None` if it is not heard.
+    #[inline]
+    pub fn heard(&self, point: Point<T>) -> Option<Heard<T>> {
+        self.cells.get(&point).copied()
+    }
+
+    /// The remaining volume at `point`, `0` if it is not heard.
+    #[inline]
+    pub fn volume(&self, point: Point<T>) -> u32 {
+        self.heard(point).map_or(0, |heard| heard.volume)
+    }
+
+    /// The direction a listener at `point` hears the sound from, as a
+    /// unit step towards a neighbour. `None` if it is not heard, or if
+    /// `point` is the source.
+    #[inline]
+    pub fn direction(&self, point: Point<T>) -> Option<Point<T>> {
+        let heard = self.heard(point)?;
+        if heard.from == point {
+            None
+        } else {
+            Some(heard.from - point)
+        }
+    }
+
+    /// An iterator over all cells that hear the sound, in no particular
+    /// order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (Point<T>, Heard<T>)> + '_ {
+        self.cells.iter().map(|(&point, &heard)| (point, heard))
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
+
+/// Spreads a sound of `volume` from `source`, through orthogonal and
+/// diagonal steps.
+///
+/// A cell hears the sound if the volume left after the cheapest path is
+/// positive. The source is heard at full volume, unless it is out of bound
+/// or `volume` is `0`.
+pub fn propagate_sound<T, MAP>(map: &MAP, source: Point<T>, volume: u32) -> SoundField<T>
+where
+    T: Coord,
+    MAP: SoundMap2D<T> + ?Sized,
+{
+    let mut cells = HashMap::new();
+    let mut queue = BinaryHeap::new();
+
+    if volume > 0 && map.is_in_bound(source) {
+        cells.insert(
+            source,
+            Heard {
+                volume,
+                from: source,
+            },
+        );
+        queue.push((volume, source));
+    }
+
+    while let Some((volume, point)) = queue.pop() {
+        if cells[&point].volume > volume {
+            continue;
+        }
+
+        for neighbour in point.neighbours_8() {
+            if !map.is_in_bound(neighbour) {
+                continue;
+            }
+            let attenuation = match map.attenuation(neighbour) {
+                Some(attenuation) => attenuation,
+                None => continue,
+            };
+            let new_volume = volume.saturating_sub(attenuation);
+            if new_volume == 0 {
+                continue;
+            }
+            let heard = Heard {
+                volume: new_volume,
+                from: point,
+            };
+            match cells.entry(neighbour) {
+                Entry::Occupied(mut entry) => {
+                    if entry.get().volume < new_volume {
+                        entry.insert(heard);
+                        queue.push((new_volume, neighbour));
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(heard);
+                    queue.push((new_volume, neighbour));
+                }
+            }
+        }
+    }
+
+    SoundField { source, cells }
+}